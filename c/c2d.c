@@ -103,6 +103,12 @@ bool c2d_drawrectsolid(float x, float y, float z, float w, float h, u32 clr) {
   return C2D_DrawRectangle(x, y, z, w, h, clr, clr, clr, clr);
 }
 
+// the gpu blends the corner colors over the rect
+bool c2d_drawrectgradient(float x, float y, float z, float w, float h,
+                          u32 clr0, u32 clr1, u32 clr2, u32 clr3) {
+  return C2D_DrawRectangle(x, y, z, w, h, clr0, clr1, clr2, clr3);
+}
+
 void c2d_raw_free_image(C2D_Image *image) {
   if (image != NULL) {
     if (image->tex != NULL) {
@@ -189,3 +195,64 @@ C2D_Image *c2d_raw_load_qrcode_from_buffer(const u8 *icon) {
   image->subtex = &g_qrcode_sub_tex;
   return image;
 }
+
+// index of the pixel inside its 8x8 tile, the inverse of tile_order
+static u32 morton_index(u32 x, u32 y) {
+  return (x & 1) | ((y & 1) << 1) | ((x & 2) << 1) | ((y & 2) << 2) |
+         ((x & 4) << 2) | ((y & 4) << 3);
+}
+
+// rgba8 pixels row by row from the top, up to 1024x1024
+C2D_Image *c2d_raw_load_rgba_image(const u8 *pixels, u32 width, u32 height) {
+  u32 tex_width = 8;
+  u32 tex_height = 8;
+  while (tex_width < width) {
+    tex_width <<= 1;
+  }
+  while (tex_height < height) {
+    tex_height <<= 1;
+  }
+  if (tex_width > 1024 || tex_height > 1024) {
+    return NULL;
+  }
+
+  C3D_Tex *tex = malloc(sizeof(C3D_Tex));
+  if (!C3D_TexInit(tex, tex_width, tex_height, GPU_RGBA8)) {
+    free(tex);
+    return NULL;
+  }
+  // keep the pixels sharp
+  C3D_TexSetFilter(tex, GPU_NEAREST, GPU_NEAREST);
+  memset(tex->data, 0, tex->size);
+
+  u32 *data = (u32 *)tex->data;
+  for (u32 y = 0; y < height; y++) {
+    for (u32 x = 0; x < width; x++) {
+      const u8 *p = pixels + 4 * (y * width + x);
+      u32 tile = (y / 8) * (tex_width / 8) + x / 8;
+      data[tile * 64 + morton_index(x % 8, y % 8)] =
+          (p[0] << 24) | (p[1] << 16) | (p[2] << 8) | p[3];
+    }
+  }
+  C3D_TexFlush(tex);
+
+  Tex3DS_SubTexture *subtex = malloc(sizeof(Tex3DS_SubTexture));
+  subtex->width = width;
+  subtex->height = height;
+  subtex->left = 0.0f;
+  subtex->top = 1.0f;
+  subtex->right = (float)width / tex_width;
+  subtex->bottom = 1.0f - (float)height / tex_height;
+
+  C2D_Image *image = malloc(sizeof(C2D_Image));
+  image->tex = tex;
+  image->subtex = subtex;
+  return image;
+}
+
+void c2d_raw_free_rgba_image(C2D_Image *image) {
+  if (image != NULL) {
+    free((Tex3DS_SubTexture *)image->subtex);
+    c2d_raw_free_image(image);
+  }
+}
@@ -4,3 +4,107 @@
   (OS_KernelConfig->app_memtype >= 6) // APPMEMTYPE. Hacky but doesn't use APT
 
 bool pl_is_n3ds() { return IS_N3DS; }
+
+static bool g_lcd_ready = false;
+
+static bool pl_lcd_init() {
+  if (!g_lcd_ready) {
+    g_lcd_ready = R_SUCCEEDED(gspLcdInit());
+  }
+  return g_lcd_ready;
+}
+
+void pl_lcd_exit() {
+  if (g_lcd_ready) {
+    gspLcdExit();
+    g_lcd_ready = false;
+  }
+}
+
+// raw luminance of the screen backlight
+bool pl_get_brightness_raw(u32 screen, u32 *brightness) {
+  return pl_lcd_init() && R_SUCCEEDED(GSPLCD_GetBrightness(screen, brightness));
+}
+
+bool pl_set_brightness_raw(u32 screen, u32 brightness) {
+  return pl_lcd_init() &&
+         R_SUCCEEDED(GSPLCD_SetBrightnessRaw(screen, brightness));
+}
+
+// brightness level 1-5, the same as the home menu
+bool pl_set_brightness(u32 screen, u32 level) {
+  return pl_lcd_init() && R_SUCCEEDED(GSPLCD_SetBrightness(screen, level));
+}
+
+bool pl_power_on_backlights() {
+  return pl_lcd_init() && R_SUCCEEDED(GSPLCD_PowerOnAllBacklights());
+}
+
+bool pl_is_sleep_allowed() { return aptIsSleepAllowed(); }
+
+void pl_set_sleep_allowed(bool allowed) { aptSetSleepAllowed(allowed); }
+
+static bool g_ptmu_ready = false;
+
+// the lid of the console, true when it is open
+bool pl_get_shell_open(bool *open) {
+  if (!g_ptmu_ready) {
+    g_ptmu_ready = R_SUCCEEDED(ptmuInit());
+  }
+  u8 state = 0;
+  if (!g_ptmu_ready || R_FAILED(PTMU_GetShellState(&state))) {
+    return false;
+  }
+  *open = state != 0;
+  return true;
+}
+
+void pl_ptmu_exit() {
+  if (g_ptmu_ready) {
+    ptmuExit();
+    g_ptmu_ready = false;
+  }
+}
+
+static aptHookCookie g_apt_cookie;
+static bool g_apt_hooked = false;
+static volatile bool g_in_home_menu = false;
+
+static void pl_apt_hook(APT_HookType hook, void *param) {
+  (void)param;
+  if (hook == APTHOOK_ONSUSPEND) {
+    g_in_home_menu = true;
+  } else if (hook == APTHOOK_ONRESTORE) {
+    g_in_home_menu = false;
+  }
+}
+
+// follow the home menu, to tell why the app was closed
+void pl_watch_home_menu() {
+  if (!g_apt_hooked) {
+    aptHook(&g_apt_cookie, pl_apt_hook, NULL);
+    g_apt_hooked = true;
+  }
+}
+
+bool pl_is_in_home_menu() { return g_in_home_menu; }
+
+// the same on every start, different on every console
+bool pl_get_console_id(u64 *id) {
+  if (R_FAILED(cfguInit())) {
+    return false;
+  }
+  bool ok = R_SUCCEEDED(CFGU_GenHashConsoleUnique(0, id));
+  cfguExit();
+  return ok;
+}
+
+// CFG_Language of the system settings
+bool pl_get_system_language(u8 *language) {
+  if (R_FAILED(cfguInit())) {
+    return false;
+  }
+  bool ok = R_SUCCEEDED(CFGU_GetSystemLanguage(language));
+  cfguExit();
+  return ok;
+}
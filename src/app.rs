@@ -1,30 +1,116 @@
 #![allow(non_snake_case)]
 
-use std::sync::{Mutex, OnceLock};
+use std::{
+    rc::Rc,
+    sync::{Mutex, OnceLock},
+};
 
 use dioxus::prelude::*;
+use log::error;
+use tokio::{sync::watch, time::Instant};
 
 use crate::{
-    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT, SCREEN_TOP_WIDTH},
-    utils::get_frame_time,
+    constant::{SAVE_HISTORY_PATH, SAVE_SETTINGS_PATH, SCREEN_HEIGHT, SCREEN_TOP_WIDTH},
+    platform::LcdScreen,
+    render::revent::KeyEventData,
+    resource::Resource,
+    settings::Settings,
+    utils::{get_frame_time, set_color_presets, sleep_micros},
+};
+
+use self::{
+    color_picker::ColorPickerScreen,
+    control_panel::ControlPanel,
+    cook_color::CookColor,
+    cook_mode::CookMode,
+    help::HelpOverlay,
+    history::{EndReason, HistoryScreen, SessionLog, UPDATE_INTERVAL},
+    input_lock::{AppLock, LockIndicator},
+    key_binding::{Action, ActionResolver},
+    recipe::{RecipeRun, RecipeStatus},
+    recipe_editor::RecipeEditor,
+    screen_config::CookScreen,
+    session_timer::SessionCountdown,
 };
 
-static APP_EXIT: OnceLock<Mutex<bool>> = OnceLock::new();
+pub mod burn_in;
+pub mod color_cycle;
+pub mod color_picker;
+pub mod control_panel;
+pub mod cook_color;
+pub mod cook_mode;
+pub mod exerciser;
+pub mod help;
+pub mod history;
+pub mod input_lock;
+pub mod key_binding;
+pub mod recipe;
+pub mod recipe_editor;
+pub mod screen_config;
+pub mod screen_fill;
+pub mod session_timer;
+pub mod stereo_3d;
+pub mod test_pattern;
+
+static APP_EXIT: OnceLock<Mutex<Option<EndReason>>> = OnceLock::new();
 
 #[derive(Clone)]
 pub struct AppExit;
 
 impl AppExit {
-    pub fn get() -> &'static Mutex<bool> {
-        APP_EXIT.get_or_init(|| Mutex::new(false))
+    pub fn get() -> &'static Mutex<Option<EndReason>> {
+        APP_EXIT.get_or_init(|| Mutex::new(None))
     }
 
-    pub fn set_exit() {
-        *Self::get().lock().unwrap() = true;
+    // the first reason is kept
+    pub fn set_exit(reason: EndReason) {
+        Self::get().lock().unwrap().get_or_insert(reason);
     }
 
     pub fn is_exit() -> bool {
-        Self::get().lock().is_ok_and(|exit| *exit)
+        Self::get().lock().is_ok_and(|exit| exit.is_some())
+    }
+
+    pub fn reason() -> Option<EndReason> {
+        Self::get().lock().ok().and_then(|exit| *exit)
+    }
+}
+
+static APP_3D: OnceLock<Mutex<bool>> = OnceLock::new();
+static SLIDER_3D: OnceLock<watch::Sender<f32>> = OnceLock::new();
+
+// the 3d option from the settings, the slider is ignored when it is off
+#[derive(Clone)]
+pub struct App3d;
+
+impl App3d {
+    pub fn get() -> &'static Mutex<bool> {
+        APP_3D.get_or_init(|| Mutex::new(true))
+    }
+
+    pub fn set_enabled(enabled: bool) {
+        *Self::get().lock().unwrap() = enabled;
+    }
+
+    pub fn is_enabled() -> bool {
+        Self::get().lock().is_ok_and(|enabled| *enabled)
+    }
+
+    fn slider() -> &'static watch::Sender<f32> {
+        SLIDER_3D.get_or_init(|| watch::channel(0.0).0)
+    }
+
+    // the slider as the renderer uses it, launch sets it when it moves
+    pub fn set_slider(value: f32) {
+        Self::slider().send_if_modified(|current| {
+            let changed = *current != value;
+            *current = value;
+            changed
+        });
+    }
+
+    pub fn watch_slider() -> watch::Receiver<f32> {
+        Self::slider().subscribe()
     }
 }
 
@@ -32,9 +118,125 @@ impl AppExit {
 #[derive(Clone, Copy, PartialEq)]
 pub struct AppFrameDuration(pub u64);
 
+// the files Main reads and writes, the snapshot tests give it a temp dir
+#[derive(Clone, PartialEq, Debug)]
+pub struct AppPaths {
+    pub settings: String,
+    pub history: String,
+}
+
+impl Default for AppPaths {
+    fn default() -> Self {
+        Self {
+            settings: SAVE_SETTINGS_PATH.to_string(),
+            history: SAVE_HISTORY_PATH.to_string(),
+        }
+    }
+}
+
+impl AppPaths {
+    pub fn in_dir(dir: &str) -> Self {
+        Self {
+            settings: format!("{}/settings.txt", dir),
+            history: format!("{}/history.txt", dir),
+        }
+    }
+}
+
 pub fn Main() -> Element {
     // app frame
     use_context_provider(|| AppFrameDuration(get_frame_time()));
+    // the console paths unless a root context points somewhere else
+    let paths = use_context_provider(|| try_consume_context::<AppPaths>().unwrap_or_default());
+    // saved settings
+    let settings = use_hook(|| {
+        SessionLog::start(&paths.history);
+        let settings = Settings::load(&paths.settings);
+        App3d::set_enabled(settings.enable_3d);
+        settings
+    });
+    // the history keeps the duration so far if the console is turned off
+    use_future(|| async {
+        loop {
+            sleep_micros(UPDATE_INTERVAL.as_micros() as u64).await;
+            SessionLog::update();
+        }
+    });
+    // what each screen shows
+    let top = use_signal(|| settings.top.clone());
+    let bottom = use_signal(|| settings.bottom.clone());
+    // the screen the keys are configuring
+    let mut selected = use_signal(|| "top");
+    // auto exit
+    let mut timer = use_signal(|| settings.timer);
+    let enable_3d = use_signal(|| settings.enable_3d);
+    // named colors, color_name_rgba looks them up too
+    let presets = use_signal(|| settings.presets.clone());
+    use_effect(move || set_color_presets(&presets.read()));
+    // button combos of the actions below, only changed in the settings file
+    let keys = use_signal(|| settings.keys.clone());
+    let mut resolver = use_signal(ActionResolver::default);
+    // the color picker replaces both screens while it is open
+    let mut picking = use_signal(|| false);
+    // the recipe editor too
+    let mut editing = use_signal(|| false);
+    // the recipe step is cooked on both screens instead of their configs
+    let mut running = use_signal(|| None::<RecipeRun>);
+    // the history replaces the control panel
+    let mut viewing_history = use_signal(|| false);
+    // select shows the key bindings over everything else
+    let mut showing_help = use_signal(|| false);
+
+    // save the settings whenever one of them changes
+    let mut saved = use_signal(|| settings.clone());
+    use_effect(move || {
+        let current = Settings {
+            top: top(),
+            bottom: bottom(),
+            timer: timer(),
+            enable_3d: enable_3d(),
+            presets: presets(),
+            keys: keys(),
+        };
+        if *saved.peek() != current {
+            if let Err(err) = current.save(&paths.settings) {
+                error!("failed to save settings: {}", err);
+            }
+            saved.set(current);
+        }
+    });
+
+    // set the backlights when their level changes, the original brightness is
+    // restored when the resource is dropped on exit
+    let resource = use_context::<Rc<Resource>>();
+    let top_brightness = use_memo(move || top().brightness);
+    let bottom_brightness = use_memo(move || bottom().brightness);
+    use_effect(move || {
+        for (screen, level) in [
+            (LcdScreen::Top, top_brightness()),
+            (LcdScreen::Bottom, bottom_brightness()),
+        ] {
+            if !resource.backlight.set_level(screen, level) {
+                error!("failed to set the {:?} backlight to {}", screen, level);
+            }
+            SessionLog::note_brightness(screen, level);
+        }
+    });
+
+    let selected_config = if selected() == "top" { top } else { bottom };
+    let (top_config, bottom_config) = match running.read().as_ref() {
+        Some(run) => (run.current().config.clone(), run.current().config.clone()),
+        None => (top(), bottom()),
+    };
+    let is_running = running.read().is_some();
+    // the history lists every mode that was on a screen
+    use_effect(move || {
+        let modes = match running.read().as_ref() {
+            Some(run) => vec![run.current().config.mode],
+            None => vec![top().mode, bottom().mode],
+        };
+        SessionLog::note_modes(&modes);
+    });
 
     rsx! {
         div {
@@ -45,32 +247,110 @@ pub fn Main() -> Element {
             color: "main-text",
             z_index: 0,
             onkeypress: move |e: KeyboardEvent| {
-                if e.data().code() == Code::Enter {
-                    AppExit::set_exit();
+                let code = e.data().code();
+                // every key event goes through the resolver, even when the action is ignored
+                let key = e.data().downcast::<KeyEventData>().map(|data| (data.2, data.3));
+                let action = key.and_then(|(held, change)| {
+                    resolver.write().resolve(&keys.peek(), held, change)
+                });
+                if action == Some(Action::Help) {
+                    let next = !*showing_help.peek();
+                    showing_help.set(next);
+                    return;
+                }
+                // the picker and the editor handle the keys themselves
+                if *picking.peek() || *editing.peek() || *viewing_history.peek() {
+                    return;
+                }
+                // the help only lists the keys
+                if *showing_help.peek() {
+                    return;
+                }
+                // so does the recipe, except for exit and the lock
+                if running.peek().is_some() {
+                    match action {
+                        Some(Action::Exit) => AppExit::set_exit(EndReason::StartKey),
+                        Some(Action::Lock) => AppLock::lock(keys.peek().combo(Action::Lock)),
+                        _ => {}
+                    }
+                    return;
+                }
+                let screen = selected();
+                let mut config = if screen == "top" { top } else { bottom };
+                match action {
+                    Some(Action::Exit) => AppExit::set_exit(EndReason::StartKey),
+                    // toggled above
+                    Some(Action::Help) => {}
+                    Some(Action::NextTimer) => timer.write().next_length(),
+                    Some(Action::ToggleCountdown) => timer.write().toggle_countdown(),
+                    // pick the screen to configure
+                    Some(Action::SelectTop) => selected.set("top"),
+                    Some(Action::SelectBottom) => selected.set("bottom"),
+                    Some(Action::NextMode) => config.write().next_mode(),
+                    // backlight of the selected screen
+                    Some(Action::Darker) => config.write().darker(),
+                    Some(Action::Brighter) => config.write().brighter(),
+                    Some(Action::Lock) => AppLock::lock(keys.peek().combo(Action::Lock)),
+                    // a bound combo that is held or waits for its release
+                    None if key.is_some_and(|(held, _)| keys.peek().action(held).is_some()) => {}
+                    None => {
+                        // only rerender when the key did something
+                        let mut next = config();
+                        next.handle_key(screen, code, e.data().is_auto_repeating());
+                        if next != *config.peek() {
+                            config.set(next);
+                        }
+                    }
                 }
             },
 
-            div {
-                "screen": "top",
-                "bg_reset": "white",
-                position: "absolute",
-                left: 0,
-                top: 0,
-                width: SCREEN_TOP_WIDTH,
-                height: SCREEN_HEIGHT,
-                background_color: "white",
+            if picking() {
+                ColorPickerScreen {
+                    initial: selected_config.peek().solid_color.rgba(),
+                    presets,
+                    // the picked color is cooked on the selected screen
+                    on_pick: move |color| {
+                        let mut config = selected_config;
+                        config.write().mode = CookMode::Solid;
+                        config.write().solid_color = CookColor::Custom(color);
+                        picking.set(false);
+                    },
+                    on_cancel: move |_| picking.set(false),
+                }
+            } else if editing() {
+                RecipeEditor {
+                    on_run: move |recipe| {
+                        running.set(Some(RecipeRun::new(recipe, Instant::now())));
+                        editing.set(false);
+                    },
+                    on_close: move |_| editing.set(false),
+                }
+            } else {
+                CookScreen { screen: "top", config: top_config }
+                CookScreen { screen: "bottom", config: bottom_config }
             }
 
-            div {
-                "screen": "bottom",
-                "bg_reset": "white",
-                position: "absolute",
-                left: 0,
-                top: 0,
-                width: SCREEN_BOTTOM_WIDTH,
-                height: SCREEN_HEIGHT,
-                background_color: "white",
+            // drawn after the mode so it stays on top
+            SessionCountdown { timer }
+            if is_running {
+                RecipeStatus { run: running }
+            } else if viewing_history() {
+                HistoryScreen { on_close: move |_| viewing_history.set(false) }
+            } else if !picking() && !editing() {
+                ControlPanel {
+                    selected,
+                    top,
+                    bottom,
+                    timer,
+                    picking,
+                    editing,
+                    viewing_history,
+                }
+            }
+            if showing_help() {
+                HelpOverlay { keys }
             }
+            LockIndicator {}
         }
     }
 }
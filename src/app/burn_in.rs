@@ -0,0 +1,159 @@
+#![allow(non_snake_case)]
+
+use std::fs;
+
+use dioxus::prelude::*;
+
+use crate::{
+    constant::{BURN_IN_IMAGES_DIR, SCREEN_HEIGHT},
+    i18n::tr_args,
+    utils::sleep_micros,
+};
+
+use super::{
+    cook_color::CookColor,
+    screen_fill::{screen_width, ScreenFill},
+};
+
+// how long the inverted image is shown
+pub const BURN_IN_LENGTHS: [u64; 7] =
+    [5 * 60, 10 * 60, 30 * 60, 3600, 2 * 3600, 4 * 3600, 8 * 3600];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BurnIn {
+    // file name in BURN_IN_IMAGES_DIR
+    pub image: Option<String>,
+    pub length_secs: u64,
+    // show the image as it is, to check it lines up with the burned in ui
+    pub preview: bool,
+}
+
+impl Default for BurnIn {
+    fn default() -> Self {
+        Self {
+            image: None,
+            length_secs: 30 * 60,
+            preview: false,
+        }
+    }
+}
+
+// png and bmp files in BURN_IN_IMAGES_DIR, sorted by name
+pub fn list_images() -> Vec<String> {
+    let mut images = fs::read_dir(BURN_IN_IMAGES_DIR)
+        .map(|dir| {
+            dir.filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| {
+                    let name = name.to_ascii_lowercase();
+                    name.ends_with(".png") || name.ends_with(".bmp")
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    images.sort();
+    images
+}
+
+impl BurnIn {
+    pub fn path(&self) -> Option<String> {
+        self.image
+            .as_ref()
+            .map(|image| format!("{}/{}", BURN_IN_IMAGES_DIR, image))
+    }
+
+    pub fn step_image(&mut self, forward: bool) {
+        let images = list_images();
+        if images.is_empty() {
+            self.image = None;
+            return;
+        }
+        let len = images.len();
+        let idx = self
+            .image
+            .as_ref()
+            .and_then(|image| images.iter().position(|name| name == image));
+        let idx = match idx {
+            Some(idx) if forward => (idx + 1) % len,
+            Some(idx) => (idx + len - 1) % len,
+            None => 0,
+        };
+        self.image = Some(images[idx].clone());
+    }
+
+    pub fn longer(&mut self) {
+        if let Some(secs) = BURN_IN_LENGTHS.iter().find(|&&s| s > self.length_secs) {
+            self.length_secs = *secs;
+        }
+    }
+
+    pub fn shorter(&mut self) {
+        if let Some(secs) = BURN_IN_LENGTHS
+            .iter()
+            .rev()
+            .find(|&&s| s < self.length_secs)
+        {
+            self.length_secs = *secs;
+        }
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.preview = !self.preview;
+    }
+}
+
+// the inverted image until the time is up, then black
+#[component]
+pub fn BurnInFill(screen: &'static str, burn_in: ReadOnlySignal<BurnIn>) -> Element {
+    let mut done = use_signal(|| false);
+
+    // the image and the length, the preview is left out
+    let picked = use_memo(move || {
+        let burn_in = burn_in.read();
+        (burn_in.image.clone(), burn_in.length_secs)
+    });
+    // sleeps until the time is up, starts over when another image or length is picked
+    let _timer = use_resource(move || async move {
+        let (_, length_secs) = picked();
+        if *done.peek() {
+            done.set(false);
+        }
+        sleep_micros(length_secs.saturating_mul(1_000_000)).await;
+        done.set(true);
+    });
+
+    let BurnIn { preview, .. } = burn_in();
+    let path = burn_in.read().path();
+
+    rsx! {
+        ScreenFill { screen, color: CookColor::Black.rgba() }
+        {match path {
+            None => rsx! {
+                div {
+                    "screen": screen,
+                    position: "absolute",
+                    left: 8,
+                    top: 8,
+                    max_width: screen_width(screen) - 16,
+                    color: "tips",
+                    {tr_args("burn_in.no_image", &[&BURN_IN_IMAGES_DIR])}
+                }
+            },
+            Some(_) if done() => rsx! {},
+            Some(path) => rsx! {
+                img {
+                    "screen": screen,
+                    "src": path,
+                    "media": "file",
+                    "invert": !preview,
+                    "scale": 1.0,
+                    position: "absolute",
+                    left: 0,
+                    top: 0,
+                    width: screen_width(screen),
+                    height: SCREEN_HEIGHT,
+                }
+            },
+        }}
+    }
+}
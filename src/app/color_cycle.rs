@@ -0,0 +1,125 @@
+#![allow(non_snake_case)]
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use tokio::time::Instant;
+
+use crate::{
+    render::tween::{use_color_tween, Easing},
+    utils::sleep_micros,
+};
+
+use super::{
+    cook_color::{CookColor, COOK_COLORS},
+    screen_fill::ScreenFill,
+};
+
+// how long one color fades into the next when fade is on
+const COLOR_FADE: Duration = Duration::from_millis(300);
+
+// seconds between two colors
+pub const CYCLE_INTERVALS: [u64; 8] = [1, 2, 5, 10, 30, 60, 300, 600];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorCycle {
+    pub colors: Vec<CookColor>,
+    pub interval_secs: u64,
+    // off keeps every color exact for burn-in checks, on eases into the next one
+    pub fade: bool,
+}
+
+impl Default for ColorCycle {
+    fn default() -> Self {
+        Self {
+            colors: COOK_COLORS.to_vec(),
+            interval_secs: 5,
+            fade: false,
+        }
+    }
+}
+
+impl ColorCycle {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    pub fn color_at(&self, idx: usize) -> u32 {
+        if self.colors.is_empty() {
+            return CookColor::Black.rgba();
+        }
+        self.colors[idx % self.colors.len()].rgba()
+    }
+
+    // add the color to the end of the cycle, or remove it if it is already in
+    pub fn toggle_color(&mut self, color: CookColor) {
+        if let Some(idx) = self.colors.iter().position(|c| *c == color) {
+            self.colors.remove(idx);
+        } else {
+            self.colors.push(color);
+        }
+    }
+
+    pub fn toggle_fade(&mut self) {
+        self.fade = !self.fade;
+    }
+
+    pub fn longer_interval(&mut self) {
+        if let Some(secs) = CYCLE_INTERVALS.iter().find(|&&s| s > self.interval_secs) {
+            self.interval_secs = *secs;
+        }
+    }
+
+    pub fn shorter_interval(&mut self) {
+        if let Some(secs) = CYCLE_INTERVALS
+            .iter()
+            .rev()
+            .find(|&&s| s < self.interval_secs)
+        {
+            self.interval_secs = *secs;
+        }
+    }
+}
+
+#[component]
+pub fn ColorCycleFill(screen: &'static str, cycle: ReadOnlySignal<ColorCycle>) -> Element {
+    let mut index = use_signal(|| 0_usize);
+    let mut changed_at = use_signal(Instant::now);
+
+    // sleeps until the next color, a changed interval counts from the last change
+    let _timer = use_resource(move || async move {
+        let (count, interval) = {
+            let cycle = cycle.read();
+            (cycle.colors.len(), cycle.interval())
+        };
+        if count < 2 {
+            return;
+        }
+        loop {
+            let left = (*changed_at.peek() + interval).saturating_duration_since(Instant::now());
+            sleep_micros(left.as_micros() as u64).await;
+            changed_at.set(Instant::now());
+            let next = (*index.peek() + 1) % count;
+            index.set(next);
+        }
+    });
+
+    let color = cycle.read().color_at(index());
+
+    rsx! {
+        if cycle.read().fade {
+            FadingFill { screen, color }
+        } else {
+            ScreenFill { screen, color }
+        }
+    }
+}
+
+#[component]
+fn FadingFill(screen: &'static str, color: u32) -> Element {
+    let color = use_color_tween(color, COLOR_FADE, Easing::InOutQuad)();
+
+    rsx! {
+        ScreenFill { screen, color }
+    }
+}
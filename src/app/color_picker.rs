@@ -0,0 +1,352 @@
+#![allow(non_snake_case)]
+
+use std::{collections::BTreeMap, rc::Rc};
+
+use dioxus::prelude::*;
+use log::warn;
+
+use crate::{
+    color::rgba,
+    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT},
+    i18n::tr,
+    platform::pl_show_swkbd,
+    resource::Resource,
+    utils::color_name_rgba,
+};
+
+use super::{
+    control_panel::PanelButton,
+    cook_color::{is_valid_preset_name, CookColor},
+    screen_fill::ScreenFill,
+};
+
+const SLIDER_LEFT: i64 = 40;
+const SLIDER_WIDTH: i64 = 220;
+const SLIDER_HEIGHT: i64 = 18;
+const SLIDER_SPACING: i64 = 24;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Hue,
+    Saturation,
+    Value,
+}
+
+pub const CHANNELS: [Channel; 6] = [
+    Channel::Red,
+    Channel::Green,
+    Channel::Blue,
+    Channel::Hue,
+    Channel::Saturation,
+    Channel::Value,
+];
+
+impl Channel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Channel::Red => tr("picker.r"),
+            Channel::Green => tr("picker.g"),
+            Channel::Blue => tr("picker.b"),
+            Channel::Hue => tr("picker.h"),
+            Channel::Saturation => tr("picker.s"),
+            Channel::Value => tr("picker.v"),
+        }
+    }
+
+    // hue in degrees, saturation and value in percent
+    pub fn max(&self) -> u16 {
+        match self {
+            Channel::Red | Channel::Green | Channel::Blue => 255,
+            Channel::Hue => 359,
+            Channel::Saturation | Channel::Value => 100,
+        }
+    }
+
+    fn bar_color(&self) -> &'static str {
+        match self {
+            Channel::Red => "red",
+            Channel::Green => "green",
+            Channel::Blue => "blue",
+            _ => "gray",
+        }
+    }
+}
+
+pub fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (u16, u8, u8) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    (
+        hue.round() as u16 % 360,
+        (saturation * 100.0).round() as u8,
+        (max * 100.0).round() as u8,
+    )
+}
+
+pub fn hsv_to_rgb(h: u16, s: u8, v: u8) -> (u8, u8, u8) {
+    let s = s.min(100) as f32 / 100.0;
+    let v = v.min(100) as f32 / 100.0;
+    let c = v * s;
+    let sector = (h % 360) as f32 / 60.0;
+    let x = c * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    let to_u8 = |c: f32| ((c + m) * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+// rgb and hsv are both kept, so the hue does not jump when the color turns gray
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorPicker {
+    pub rgb: [u8; 3],
+    pub hsv: (u16, u8, u8),
+    pub channel: Channel,
+}
+
+impl ColorPicker {
+    pub fn new(color: u32) -> Self {
+        let rgb = [color as u8, (color >> 8) as u8, (color >> 16) as u8];
+        Self {
+            rgb,
+            hsv: rgb_to_hsv(rgb[0], rgb[1], rgb[2]),
+            channel: Channel::Red,
+        }
+    }
+
+    pub fn rgba(&self) -> u32 {
+        rgba(self.rgb[0], self.rgb[1], self.rgb[2], 0xff)
+    }
+
+    pub fn get(&self, channel: Channel) -> u16 {
+        match channel {
+            Channel::Red => self.rgb[0] as u16,
+            Channel::Green => self.rgb[1] as u16,
+            Channel::Blue => self.rgb[2] as u16,
+            Channel::Hue => self.hsv.0,
+            Channel::Saturation => self.hsv.1 as u16,
+            Channel::Value => self.hsv.2 as u16,
+        }
+    }
+
+    pub fn set(&mut self, channel: Channel, value: u16) {
+        let value = value.min(channel.max());
+        match channel {
+            Channel::Red | Channel::Green | Channel::Blue => {
+                let idx = channel as usize;
+                self.rgb[idx] = value as u8;
+                let (h, s, v) = rgb_to_hsv(self.rgb[0], self.rgb[1], self.rgb[2]);
+                // gray and black have no hue, black has no saturation either
+                let h = if s == 0 || v == 0 { self.hsv.0 } else { h };
+                let s = if v == 0 { self.hsv.1 } else { s };
+                self.hsv = (h, s, v);
+            }
+            Channel::Hue | Channel::Saturation | Channel::Value => {
+                match channel {
+                    Channel::Hue => self.hsv.0 = value,
+                    Channel::Saturation => self.hsv.1 = value as u8,
+                    _ => self.hsv.2 = value as u8,
+                }
+                let (r, g, b) = hsv_to_rgb(self.hsv.0, self.hsv.1, self.hsv.2);
+                self.rgb = [r, g, b];
+            }
+        }
+    }
+
+    pub fn step(&mut self, channel: Channel, delta: i32) {
+        let value = (self.get(channel) as i32 + delta).clamp(0, channel.max() as i32);
+        self.set(channel, value as u16);
+    }
+
+    pub fn handle_key(&mut self, code: Code) {
+        let idx = CHANNELS
+            .iter()
+            .position(|c| *c == self.channel)
+            .unwrap_or(0);
+        match code {
+            Code::ArrowUp => self.channel = CHANNELS[(idx + CHANNELS.len() - 1) % CHANNELS.len()],
+            Code::ArrowDown => self.channel = CHANNELS[(idx + 1) % CHANNELS.len()],
+            Code::ArrowLeft => self.step(self.channel, -1),
+            Code::ArrowRight => self.step(self.channel, 1),
+            _ => {}
+        }
+    }
+}
+
+#[component]
+fn Slider(mut picker: Signal<ColorPicker>, channel: Channel, top: i64) -> Element {
+    let value = picker.read().get(channel);
+    let selected = picker.read().channel == channel;
+    let max = channel.max() as i64;
+    let filled = value as i64 * SLIDER_WIDTH / max;
+
+    rsx! {
+        div {
+            position: "absolute",
+            left: 8,
+            top,
+            color: if selected { "main-text" } else { "tips" },
+            "{channel.label()}"
+        }
+        div {
+            position: "absolute",
+            left: SLIDER_LEFT,
+            top,
+            width: SLIDER_WIDTH,
+            height: SLIDER_HEIGHT,
+            background_color: if selected { "selected_bg_light" } else { "selected_bg" },
+            // tap anywhere on the bar to jump to that value
+            onmousedown: move |e: MouseEvent| {
+                let x = e.screen_coordinates().x as i64 - SLIDER_LEFT;
+                let value = (x.clamp(0, SLIDER_WIDTH) * max + SLIDER_WIDTH / 2) / SLIDER_WIDTH;
+                let mut next = *picker.peek();
+                next.channel = channel;
+                next.set(channel, value as u16);
+                picker.set(next);
+            },
+            div {
+                width: filled,
+                height: SLIDER_HEIGHT,
+                background_color: channel.bar_color(),
+            }
+        }
+        div {
+            position: "absolute",
+            left: SLIDER_LEFT + SLIDER_WIDTH + 8,
+            top,
+            "{value}"
+        }
+    }
+}
+
+// sliders on the bottom screen, the top screen shows the color
+#[component]
+pub fn ColorPickerScreen(
+    initial: u32,
+    mut presets: Signal<BTreeMap<String, u32>>,
+    on_pick: EventHandler<u32>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let resource = use_context::<Rc<Resource>>();
+    let mut picker = use_signal(|| ColorPicker::new(initial));
+    let color = picker.read().rgba();
+    let buttons_top = SLIDER_SPACING * CHANNELS.len() as i64 + 8;
+
+    rsx! {
+        ScreenFill { screen: "top", color }
+        ScreenFill { screen: "bottom", color: color_name_rgba("main_bg") }
+        div {
+            "screen": "bottom",
+            position: "absolute",
+            left: 0,
+            top: 0,
+            width: SCREEN_BOTTOM_WIDTH,
+            height: SCREEN_HEIGHT,
+            color: "main-text",
+            z_index: 2,
+            onkeypress: move |e: KeyboardEvent| match e.data().code() {
+                // only the sliders follow a held button
+                Code::KeyA | Code::KeyB if e.data().is_auto_repeating() => {}
+                Code::KeyA => on_pick.call(picker.peek().rgba()),
+                Code::KeyB => on_cancel.call(()),
+                code => {
+                    let mut next = *picker.peek();
+                    next.handle_key(code);
+                    if next != *picker.peek() {
+                        picker.set(next);
+                    }
+                }
+            },
+
+            for (idx, channel) in CHANNELS.into_iter().enumerate() {
+                Slider { picker, channel, top: 8 + SLIDER_SPACING * idx as i64 }
+            }
+            div {
+                "scale": 0.5,
+                display: "flex",
+                position: "absolute",
+                left: 4,
+                top: buttons_top,
+                PanelButton {
+                    label: tr("picker.use"),
+                    value: CookColor::Custom(color).label(),
+                    onclick: move |_| on_pick.call(picker.peek().rgba()),
+                }
+                PanelButton {
+                    label: tr("common.save"),
+                    value: tr("picker.preset"),
+                    onclick: move |_| {
+                        let Some(name) = pl_show_swkbd(&resource, "") else {
+                            return;
+                        };
+                        let name = name.trim().to_string();
+                        if is_valid_preset_name(&name) {
+                            let color = picker.peek().rgba();
+                            presets.write().insert(name, color);
+                        } else {
+                            warn!("invalid preset name {}", name);
+                        }
+                    },
+                }
+                PanelButton {
+                    label: tr("picker.cancel"),
+                    value: "",
+                    onclick: move |_| on_cancel.call(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primaries_to_hsv() {
+        assert_eq!(rgb_to_hsv(0xff, 0x00, 0x00), (0, 100, 100));
+        assert_eq!(rgb_to_hsv(0x00, 0xff, 0x00), (120, 100, 100));
+        assert_eq!(rgb_to_hsv(0x00, 0x00, 0xff), (240, 100, 100));
+        assert_eq!(rgb_to_hsv(0x00, 0x00, 0x00), (0, 0, 0));
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for (r, g, b) in [(0xff, 0x80, 0x00), (0x12, 0x34, 0x56), (0x80, 0x80, 0x80)] {
+            let (h, s, v) = rgb_to_hsv(r, g, b);
+            let (r2, g2, b2) = hsv_to_rgb(h, s, v);
+            for (a, b) in [(r, r2), (g, g2), (b, b2)] {
+                assert!((a as i32 - b as i32).abs() <= 3, "{:?}", (r, g, b));
+            }
+        }
+    }
+
+    #[test]
+    fn hue_is_kept_for_black() {
+        let mut picker = ColorPicker::new(rgba(0xff, 0x00, 0x00, 0xff));
+        picker.set(Channel::Hue, 200);
+        picker.set(Channel::Value, 0);
+        assert_eq!(picker.rgb, [0, 0, 0]);
+        picker.set(Channel::Green, 0);
+        assert_eq!(picker.hsv, (200, 100, 0));
+    }
+}
@@ -0,0 +1,207 @@
+#![allow(non_snake_case)]
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use tokio::time::Instant;
+
+use crate::{
+    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT},
+    i18n::{tr, tr_name},
+    platform::MAX_BACKLIGHT_LEVEL,
+    render::tween::{use_tween, Easing},
+    utils::sleep_micros,
+};
+
+use super::{screen_config::ScreenConfig, session_timer::SessionTimer};
+
+// hide the panel when it is not touched for a while
+const PANEL_TIMEOUT: Duration = Duration::from_secs(10);
+
+const BUTTON_WIDTH: i64 = 96;
+const BUTTON_HEIGHT: i64 = 48;
+const BUTTON_MARGIN: i64 = 4;
+const PANEL_ROWS: i64 = 3;
+const PANEL_HEIGHT: i64 = PANEL_ROWS * (BUTTON_HEIGHT + 2 * BUTTON_MARGIN) + 2 * BUTTON_MARGIN;
+// how long the panel takes to slide in or out
+const PANEL_SLIDE: Duration = Duration::from_millis(400);
+
+#[component]
+pub fn PanelButton(
+    #[props(into)] label: String,
+    #[props(into)] value: String,
+    onclick: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div {
+            display: "flex",
+            flex_direction: "column",
+            justify_content: "center",
+            align_items: "center",
+            width: BUTTON_WIDTH,
+            height: BUTTON_HEIGHT,
+            margin: BUTTON_MARGIN,
+            background_color: "selected_bg",
+            onclick: move |_| onclick.call(()),
+            div { "{label}" }
+            div { color: "tips", "{value}" }
+        }
+    }
+}
+
+#[component]
+pub fn ControlPanel(
+    mut selected: Signal<&'static str>,
+    top: Signal<ScreenConfig>,
+    bottom: Signal<ScreenConfig>,
+    mut timer: Signal<SessionTimer>,
+    mut picking: Signal<bool>,
+    mut editing: Signal<bool>,
+    mut viewing_history: Signal<bool>,
+) -> Element {
+    let mut visible = use_signal(|| true);
+    let mut touched_at = use_signal(Instant::now);
+
+    // hides PANEL_TIMEOUT after the last touch, a touch starts it over
+    let _timer = use_resource(move || async move {
+        let left = (touched_at() + PANEL_TIMEOUT).saturating_duration_since(Instant::now());
+        sleep_micros(left.as_micros() as u64).await;
+        if *visible.peek() {
+            visible.set(false);
+        }
+    });
+
+    // slides down out of the screen and fades while hiding
+    let (top_target, opacity_target) = if visible() {
+        (SCREEN_HEIGHT - PANEL_HEIGHT, 1.0)
+    } else {
+        (SCREEN_HEIGHT, 0.0)
+    };
+    let panel_top = use_tween(top_target as f64, PANEL_SLIDE, Easing::OutExpo);
+    let panel_opacity = use_tween(opacity_target, PANEL_SLIDE, Easing::OutQuad);
+
+    if !visible() && panel_top() >= SCREEN_HEIGHT as f64 {
+        // invisible, a tap anywhere on the bottom screen brings the panel back
+        return rsx! {
+            div {
+                "screen": "bottom",
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: SCREEN_BOTTOM_WIDTH,
+                height: SCREEN_HEIGHT,
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    visible.set(true);
+                },
+            }
+        };
+    }
+
+    let screen = selected();
+    let mut config = if screen == "top" { top } else { bottom };
+    let ScreenConfig {
+        mode,
+        solid_color,
+        brightness,
+        ..
+    } = config();
+
+    rsx! {
+        div {
+            "screen": "bottom",
+            "scale": 0.5,
+            display: "flex",
+            flex_wrap: "wrap",
+            position: "absolute",
+            left: 0,
+            top: panel_top().round(),
+            width: SCREEN_BOTTOM_WIDTH,
+            height: PANEL_HEIGHT,
+            opacity: panel_opacity(),
+            padding: BUTTON_MARGIN,
+            color: "main-text",
+            background_color: "panel_bg",
+            z_index: 2,
+            onclick: move |_| touched_at.set(Instant::now()),
+
+            PanelButton {
+                label: tr("panel.screen"),
+                value: tr_name("screen", screen),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    selected.set(if screen == "top" { "bottom" } else { "top" });
+                },
+            }
+            PanelButton {
+                label: tr("panel.mode"),
+                value: mode.label(),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    config.write().next_mode();
+                },
+            }
+            PanelButton {
+                label: tr("panel.color"),
+                value: solid_color.label(),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    let next = config.peek().solid_color.step(true);
+                    config.write().solid_color = next;
+                },
+            }
+            PanelButton {
+                label: tr("panel.timer"),
+                value: timer().length_label(),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    timer.write().next_length();
+                },
+            }
+            PanelButton {
+                label: tr("panel.countdown"),
+                value: tr(if timer().show_countdown { "common.on" } else { "common.off" }),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    timer.write().toggle_countdown();
+                },
+            }
+            PanelButton {
+                label: tr("panel.brightness"),
+                value: match brightness {
+                    0 => tr("panel.brightness_system").to_string(),
+                    level => level.to_string(),
+                },
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    let next = (config.peek().brightness + 1) % (MAX_BACKLIGHT_LEVEL + 1);
+                    config.write().brightness = next;
+                },
+            }
+            PanelButton {
+                label: tr("panel.picker"),
+                value: tr("panel.picker_value"),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    picking.set(true);
+                },
+            }
+            PanelButton {
+                label: tr("panel.recipe"),
+                value: tr("panel.recipe_value"),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    editing.set(true);
+                },
+            }
+            PanelButton {
+                label: tr("panel.history"),
+                value: tr("panel.history_value"),
+                onclick: move |_| {
+                    touched_at.set(Instant::now());
+                    viewing_history.set(true);
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    color::rgba,
+    i18n::tr_name,
+    utils::{color_name_rgba, get_color_presets, is_builtin_color_name},
+};
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum CookColor {
+    #[default]
+    White,
+    Red,
+    Green,
+    Blue,
+    Black,
+    Custom(u32),
+}
+
+// the colors that can be picked with the d-pad, the presets follow them
+pub const COOK_COLORS: [CookColor; 5] = [
+    CookColor::White,
+    CookColor::Red,
+    CookColor::Green,
+    CookColor::Blue,
+    CookColor::Black,
+];
+
+// a name for a color picker preset, it must not look like any other color value
+pub fn is_valid_preset_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        && !is_builtin_color_name(name)
+}
+
+impl CookColor {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CookColor::White => "white",
+            CookColor::Red => "red",
+            CookColor::Green => "green",
+            CookColor::Blue => "blue",
+            CookColor::Black => "black",
+            CookColor::Custom(_) => "custom",
+        }
+    }
+
+    pub fn rgba(&self) -> u32 {
+        match self {
+            CookColor::Custom(color) => *color,
+            _ => color_name_rgba(self.name()),
+        }
+    }
+
+    pub fn custom(r: u8, g: u8, b: u8, a: u8) -> Self {
+        CookColor::Custom(rgba(r, g, b, a))
+    }
+
+    pub fn step(&self, forward: bool) -> Self {
+        self.step_with(forward, &get_color_presets())
+    }
+
+    // step through COOK_COLORS and the presets, another custom color goes back to the first one
+    pub fn step_with(&self, forward: bool, presets: &BTreeMap<String, u32>) -> Self {
+        let colors = COOK_COLORS
+            .into_iter()
+            .chain(presets.values().map(|color| CookColor::Custom(*color)))
+            .collect::<Vec<_>>();
+        let len = colors.len();
+        match colors.iter().position(|c| c == self) {
+            Some(idx) if forward => colors[(idx + 1) % len],
+            Some(idx) => colors[(idx + len - 1) % len],
+            None => colors[0],
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::parse_with(text, &get_color_presets())
+    }
+
+    // a color name, a preset name or "#rrggbbaa"
+    pub fn parse_with(text: &str, presets: &BTreeMap<String, u32>) -> Option<Self> {
+        match text {
+            "white" => Some(CookColor::White),
+            "red" => Some(CookColor::Red),
+            "green" => Some(CookColor::Green),
+            "blue" => Some(CookColor::Blue),
+            "black" => Some(CookColor::Black),
+            _ => {
+                if let Some(color) = presets.get(text) {
+                    return Some(CookColor::Custom(*color));
+                }
+                let hex = text.strip_prefix('#')?;
+                if hex.len() != 8 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+                let value = u32::from_str_radix(hex, 16).ok()?;
+                Some(CookColor::custom(
+                    (value >> 24) as u8,
+                    (value >> 16) as u8,
+                    (value >> 8) as u8,
+                    value as u8,
+                ))
+            }
+        }
+    }
+
+    // the built in names in the ui language, custom colors as their preset name or text
+    pub fn label(&self) -> String {
+        tr_name("color", &self.to_text_with(&get_color_presets()))
+    }
+
+    pub fn to_text(&self) -> String {
+        self.to_text_with(&BTreeMap::new())
+    }
+
+    // a custom color with a preset is saved as its name
+    pub fn to_text_with(&self, presets: &BTreeMap<String, u32>) -> String {
+        let CookColor::Custom(color) = self else {
+            return self.name().to_string();
+        };
+        match presets.iter().find(|(_, preset)| *preset == color) {
+            Some((name, _)) => name.clone(),
+            None => format!(
+                "#{:02x}{:02x}{:02x}{:02x}",
+                color & 0xff,
+                (color >> 8) & 0xff,
+                (color >> 16) & 0xff,
+                (color >> 24) & 0xff
+            ),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::i18n::tr_name;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum CookMode {
+    // fill both screens with one color
+    #[default]
+    Solid,
+    // step through the color cycle
+    ColorCycle,
+    // flash noise or primaries to free stuck subpixels
+    Exerciser,
+    // diagnostic patterns for dead pixels
+    TestPattern,
+    // the inverted image of a burned in ui
+    BurnIn,
+    // depth and crosstalk patterns for the 3d screen
+    Stereo3d,
+}
+
+impl CookMode {
+    pub const ALL: [CookMode; 6] = [
+        CookMode::Solid,
+        CookMode::ColorCycle,
+        CookMode::Exerciser,
+        CookMode::TestPattern,
+        CookMode::BurnIn,
+        CookMode::Stereo3d,
+    ];
+
+    pub fn next(&self) -> Self {
+        let idx = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CookMode::Solid => "solid",
+            CookMode::ColorCycle => "color_cycle",
+            CookMode::Exerciser => "exerciser",
+            CookMode::TestPattern => "test_pattern",
+            CookMode::BurnIn => "burn_in",
+            CookMode::Stereo3d => "stereo_3d",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr_name("mode", self.name())
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL.iter().find(|mode| mode.name() == text).copied()
+    }
+}
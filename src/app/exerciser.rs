@@ -0,0 +1,179 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::{
+    color::rgba,
+    constant::SCREEN_HEIGHT,
+    render::frame::use_animation_frame,
+    utils::{current_time, xorshift32},
+};
+
+use super::{
+    cook_color::CookColor,
+    screen_fill::{screen_width, ScreenFill},
+};
+
+const PRIMARIES: [u32; 3] = [
+    rgba(0xff, 0x00, 0x00, 0xff),
+    rgba(0x00, 0xff, 0x00, 0xff),
+    rgba(0x00, 0x00, 0xff, 0xff),
+];
+
+// noise cell size in pixels, keeps a full screen below the c2d object limit
+const NOISE_CELL: i64 = 10;
+
+const PATCH_SIZES: [i64; 5] = [4, 8, 16, 32, 64];
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ExerciserStyle {
+    // random colors every frame
+    #[default]
+    Noise,
+    // red, green and blue in turn every frame
+    Primaries,
+}
+
+// a small square flashing around a known bad pixel
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ExerciserPatch {
+    // center of the patch
+    pub x: i64,
+    pub y: i64,
+    pub size: i64,
+}
+
+impl ExerciserPatch {
+    fn new(screen: &str) -> Self {
+        Self {
+            x: screen_width(screen) / 2,
+            y: SCREEN_HEIGHT / 2,
+            size: PATCH_SIZES[0],
+        }
+    }
+
+    fn clamp(&mut self, screen: &str) {
+        let half = self.size / 2;
+        self.x = self.x.clamp(half, screen_width(screen) - half);
+        self.y = self.y.clamp(half, SCREEN_HEIGHT - half);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Exerciser {
+    pub style: ExerciserStyle,
+    // flash the whole screen if there is no patch
+    pub patch: Option<ExerciserPatch>,
+}
+
+impl Exerciser {
+    pub fn toggle_style(&mut self) {
+        self.style = match self.style {
+            ExerciserStyle::Noise => ExerciserStyle::Primaries,
+            ExerciserStyle::Primaries => ExerciserStyle::Noise,
+        };
+    }
+
+    // whole screen -> 4px patch -> ... -> 64px patch -> whole screen
+    pub fn next_patch(&mut self, screen: &str) {
+        self.patch = match self.patch {
+            None => Some(ExerciserPatch::new(screen)),
+            Some(mut patch) => PATCH_SIZES
+                .iter()
+                .find(|&&size| size > patch.size)
+                .map(|&size| {
+                    patch.size = size;
+                    patch.clamp(screen);
+                    patch
+                }),
+        };
+    }
+
+    pub fn move_patch(&mut self, screen: &str, dx: i64, dy: i64) {
+        if let Some(patch) = self.patch.as_mut() {
+            patch.x += dx;
+            patch.y += dy;
+            patch.clamp(screen);
+        }
+    }
+}
+
+#[component]
+fn Flash(
+    screen: &'static str,
+    left: i64,
+    top: i64,
+    width: i64,
+    height: i64,
+    style: ExerciserStyle,
+    tick: u32,
+) -> Element {
+    match style {
+        ExerciserStyle::Primaries => rsx! {
+            div {
+                "screen": screen,
+                position: "absolute",
+                left,
+                top,
+                width,
+                height,
+                background_color: PRIMARIES[tick as usize % PRIMARIES.len()] as i64,
+            }
+        },
+        ExerciserStyle::Noise => rsx! {
+            canvas {
+                "screen": screen,
+                "pattern": "noise",
+                "seed": xorshift32(tick) as i64,
+                "cell": NOISE_CELL,
+                position: "absolute",
+                left,
+                top,
+                width,
+                height,
+            }
+        },
+    }
+}
+
+#[component]
+pub fn ExerciserFill(screen: &'static str, exerciser: Exerciser) -> Element {
+    // different noise on the two screens
+    let mut tick = use_signal(|| current_time() as u32 ^ screen_width(screen) as u32);
+
+    // change the colors on every frame
+    use_animation_frame(move |_| {
+        let next = tick.peek().wrapping_add(1);
+        tick.set(next);
+    });
+
+    let Exerciser { style, patch } = exerciser;
+    let tick = tick();
+
+    match patch {
+        None => rsx! {
+            ScreenFill { screen, color: CookColor::Black.rgba() }
+            Flash {
+                screen,
+                left: 0,
+                top: 0,
+                width: screen_width(screen),
+                height: SCREEN_HEIGHT,
+                style,
+                tick,
+            }
+        },
+        Some(ExerciserPatch { x, y, size }) => rsx! {
+            ScreenFill { screen, color: CookColor::Black.rgba() }
+            Flash {
+                screen,
+                left: x - size / 2,
+                top: y - size / 2,
+                width: size,
+                height: size,
+                style,
+                tick,
+            }
+        },
+    }
+}
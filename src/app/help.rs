@@ -0,0 +1,107 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::{
+    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT},
+    i18n::{tr, tr_name},
+    render::revent::BUTTONS,
+    utils::color_name_rgba,
+};
+
+use super::{
+    key_binding::{combo_text, Action, KeyBindings},
+    screen_fill::ScreenFill,
+};
+
+const ROW_HEIGHT: i64 = 16;
+
+// the buttons that change the settings of the current mode
+const MODE_CODES: [Code; 5] = [
+    Code::KeyA,
+    Code::ArrowLeft,
+    Code::ArrowRight,
+    Code::ArrowUp,
+    Code::ArrowDown,
+];
+
+// the combo and the text of every action, then the buttons of the mode
+pub fn help_rows(keys: &KeyBindings) -> Vec<(String, String)> {
+    let mode_buttons = BUTTONS
+        .iter()
+        .filter(|(_, _, code)| MODE_CODES.contains(code))
+        .map(|(_, name, _)| *name)
+        .collect::<Vec<_>>()
+        .join("/");
+    Action::ALL
+        .iter()
+        .map(|action| {
+            (
+                combo_text(keys.combo(*action)),
+                tr_name("help", action.name()),
+            )
+        })
+        .chain(std::iter::once((
+            mode_buttons,
+            tr("help.mode_keys").to_string(),
+        )))
+        .collect()
+}
+
+// the key bindings over the bottom screen, whatever mode is cooking
+#[component]
+pub fn HelpOverlay(keys: ReadOnlySignal<KeyBindings>) -> Element {
+    let rows = help_rows(&keys.read())
+        .into_iter()
+        .enumerate()
+        .map(|(row, (buttons, help))| (row, format!("{}: {}", buttons, help)))
+        .collect::<Vec<_>>();
+
+    rsx! {
+        ScreenFill { screen: "bottom", color: color_name_rgba("main_bg") }
+        div {
+            "screen": "bottom",
+            position: "absolute",
+            left: 0,
+            top: 0,
+            width: SCREEN_BOTTOM_WIDTH,
+            height: SCREEN_HEIGHT,
+            color: "main-text",
+            z_index: 3,
+
+            div {
+                position: "absolute",
+                left: 8,
+                top: 4,
+                {tr("help.title")}
+            }
+            for (row, line) in rows {
+                div {
+                    position: "absolute",
+                    left: 8,
+                    top: 4 + ROW_HEIGHT * (row as i64 + 1),
+                    max_width: SCREEN_BOTTOM_WIDTH - 16,
+                    color: "tips",
+                    "{line}"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::platform::KeyPad;
+
+    use super::*;
+
+    #[test]
+    fn rows_follow_the_bindings() {
+        let mut keys = KeyBindings::default();
+        keys.set(Action::Exit, KeyPad::L | KeyPad::R | KeyPad::START);
+        let rows = help_rows(&keys);
+        assert_eq!(rows.len(), Action::ALL.len() + 1);
+        assert_eq!(rows[0].0, "L+R+Start");
+        assert_eq!(rows[Action::ALL.len()].0, "A/Left/Right/Up/Down");
+    }
+}
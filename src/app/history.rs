@@ -0,0 +1,443 @@
+#![allow(non_snake_case)]
+
+//! one session per line in AppPaths::history, SAVE_HISTORY_PATH on the console
+//!
+//! ```text
+//! console=0123abcd;start=2024-10-28 20.15.03.120;duration=60;modes=solid;brightness=3,0;end=timer
+//! ```
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use dioxus::prelude::*;
+use log::{error, warn};
+
+use crate::{
+    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT},
+    i18n::{tr, tr_args, tr_name},
+    platform::{get_console_id, is_in_home_menu, LcdScreen},
+    utils::{color_name_rgba, format_duration, get_current_format_time},
+};
+
+use super::{
+    control_panel::PanelButton, cook_mode::CookMode, screen_fill::ScreenFill, AppExit, AppPaths,
+};
+
+const ROW_HEIGHT: i64 = 16;
+// newest sessions shown under the totals
+const RECENT_SESSIONS: usize = 5;
+// how often the running session is rewritten
+pub const UPDATE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EndReason {
+    // the session timer ran out
+    Timer,
+    // the last step of a recipe was over
+    Recipe,
+    StartKey,
+    // closed from the home menu
+    HomeMenu,
+    // closed by the system without the home menu, the power button
+    Power,
+}
+
+impl EndReason {
+    pub const ALL: [EndReason; 5] = [
+        EndReason::Timer,
+        EndReason::Recipe,
+        EndReason::StartKey,
+        EndReason::HomeMenu,
+        EndReason::Power,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EndReason::Timer => "timer",
+            EndReason::Recipe => "recipe",
+            EndReason::StartKey => "start",
+            EndReason::HomeMenu => "home_menu",
+            EndReason::Power => "power",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr_name("end", self.name())
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|reason| reason.name() == text)
+            .copied()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SessionRecord {
+    // get_console_id, empty if it could not be read
+    pub console: String,
+    // get_current_format_time when the app started
+    pub start: String,
+    pub duration_secs: u64,
+    // every mode shown during the session, in the order they were first used
+    pub modes: Vec<CookMode>,
+    // top and bottom backlight level at the end, 0 is the system level
+    pub brightness: [u8; 2],
+    pub end: EndReason,
+}
+
+impl SessionRecord {
+    pub fn parse(line: &str) -> Option<Self> {
+        let values = line
+            .split(';')
+            .filter_map(|field| field.split_once('='))
+            .collect::<BTreeMap<_, _>>();
+        let modes = values.get("modes")?;
+        let modes = modes
+            .split(',')
+            .filter(|mode| !mode.is_empty())
+            .map(CookMode::parse)
+            .collect::<Option<Vec<_>>>()?;
+        let (top, bottom) = values.get("brightness")?.split_once(',')?;
+        Some(Self {
+            console: values.get("console")?.to_string(),
+            start: values.get("start")?.to_string(),
+            duration_secs: values.get("duration")?.parse().ok()?,
+            modes,
+            brightness: [top.parse().ok()?, bottom.parse().ok()?],
+            end: EndReason::parse(values.get("end")?)?,
+        })
+    }
+
+    pub fn to_line(&self) -> String {
+        let modes = self
+            .modes
+            .iter()
+            .map(|mode| mode.name())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "console={};start={};duration={};modes={};brightness={},{};end={}",
+            self.console,
+            self.start,
+            self.duration_secs,
+            modes,
+            self.brightness[0],
+            self.brightness[1],
+            self.end.name()
+        )
+    }
+}
+
+// broken lines are skipped, a missing file is an empty history
+pub fn load_history(path: &str) -> Vec<SessionRecord> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!("failed to read history: {}", err);
+            }
+            return vec![];
+        }
+    };
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let record = SessionRecord::parse(line);
+            if record.is_none() {
+                warn!("invalid history line {}", line);
+            }
+            record
+        })
+        .collect()
+}
+
+// appends the record, or replaces it when its line starts at offset, returns where it starts
+pub fn write_history(path: &str, offset: Option<u64>, record: &SessionRecord) -> io::Result<u64> {
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?;
+    let len = file.metadata()?.len();
+    // the file could have been cut since the last write
+    let offset = offset.filter(|offset| *offset <= len).unwrap_or(len);
+    file.set_len(offset)?;
+    file.seek(SeekFrom::Start(offset))?;
+    writeln!(file, "{}", record.to_line())?;
+    Ok(offset)
+}
+
+// (console, seconds, sessions), sorted by console
+pub fn console_totals(records: &[SessionRecord]) -> Vec<(String, u64, usize)> {
+    let mut totals = BTreeMap::new();
+    for record in records {
+        let total = totals.entry(record.console.clone()).or_insert((0, 0));
+        total.0 += record.duration_secs;
+        total.1 += 1;
+    }
+    totals
+        .into_iter()
+        .map(|(console, (secs, count))| (console, secs, count))
+        .collect()
+}
+
+struct CurrentSession {
+    path: String,
+    started_at: Instant,
+    record: SessionRecord,
+    // where its line starts in the history file, None until it was written
+    offset: Option<u64>,
+}
+
+impl CurrentSession {
+    fn write(&mut self) {
+        self.record.duration_secs = self.started_at.elapsed().as_secs();
+        match write_history(&self.path, self.offset, &self.record) {
+            Ok(offset) => self.offset = Some(offset),
+            Err(err) => error!("failed to save history: {}", err),
+        }
+    }
+}
+
+static SESSION_LOG: OnceLock<Mutex<Option<CurrentSession>>> = OnceLock::new();
+
+// the running session, its line in the history is written when the app starts and
+// rewritten while it runs, so a console turned off still leaves a record
+#[derive(Clone)]
+pub struct SessionLog;
+
+impl SessionLog {
+    fn get() -> &'static Mutex<Option<CurrentSession>> {
+        SESSION_LOG.get_or_init(|| Mutex::new(None))
+    }
+
+    pub fn start(path: &str) {
+        let record = SessionRecord {
+            console: get_console_id().unwrap_or_default(),
+            start: get_current_format_time().to_string(),
+            duration_secs: 0,
+            modes: vec![],
+            brightness: [0, 0],
+            // what is left when the console is turned off, replaced when the app ends
+            end: EndReason::Power,
+        };
+        let mut session = CurrentSession {
+            path: path.to_string(),
+            started_at: Instant::now(),
+            record,
+            offset: None,
+        };
+        session.write();
+        *Self::get().lock().unwrap() = Some(session);
+    }
+
+    pub fn note_modes(modes: &[CookMode]) {
+        if let Some(session) = Self::get().lock().unwrap().as_mut() {
+            for mode in modes {
+                if !session.record.modes.contains(mode) {
+                    session.record.modes.push(*mode);
+                }
+            }
+        }
+    }
+
+    pub fn note_brightness(screen: LcdScreen, level: u8) {
+        if let Some(session) = Self::get().lock().unwrap().as_mut() {
+            let idx = if screen == LcdScreen::Top { 0 } else { 1 };
+            session.record.brightness[idx] = level;
+        }
+    }
+
+    // the duration, modes and backlights so far, Main calls it every UPDATE_INTERVAL
+    pub fn update() {
+        if let Some(session) = Self::get().lock().unwrap().as_mut() {
+            session.write();
+        }
+    }
+
+    // called once launch returns, the system closed the app if it did not exit by itself
+    pub fn save() {
+        let Some(mut session) = Self::get().lock().unwrap().take() else {
+            return;
+        };
+        session.record.end = AppExit::reason().unwrap_or(if is_in_home_menu() {
+            EndReason::HomeMenu
+        } else {
+            EndReason::Power
+        });
+        session.write();
+    }
+}
+
+// totals per console and the latest sessions on the bottom screen
+#[component]
+pub fn HistoryScreen(on_close: EventHandler<()>) -> Element {
+    let paths = use_context::<AppPaths>();
+    let records = use_hook(|| load_history(&paths.history));
+    let this_console = use_hook(|| get_console_id().unwrap_or_default());
+
+    let totals = console_totals(&records)
+        .into_iter()
+        .map(|(console, secs, count)| {
+            let marker = if console == this_console {
+                tr("history.this_console")
+            } else {
+                ""
+            };
+            let console = match console.as_str() {
+                "" => tr("common.unknown").to_string(),
+                id => id.chars().take(8).collect(),
+            };
+            let hours = format!("{:.1}", secs as f64 / 3600.0);
+            tr_args("history.total", &[&console, &marker, &hours, &count])
+        })
+        .collect::<Vec<_>>();
+    let recent = records
+        .iter()
+        .rev()
+        .take(RECENT_SESSIONS)
+        .map(|record| {
+            // "2024-10-28 20.15"
+            let start = record.start.get(..16).unwrap_or(&record.start);
+            format!(
+                "{} {} {}",
+                start,
+                format_duration(record.duration_secs),
+                record.end.label()
+            )
+        })
+        .collect::<Vec<_>>();
+    let totals_len = totals.len();
+    let lines = totals
+        .into_iter()
+        .chain(std::iter::once(String::new()))
+        .chain(recent)
+        .enumerate()
+        .collect::<Vec<_>>();
+
+    rsx! {
+        ScreenFill { screen: "bottom", color: color_name_rgba("main_bg") }
+        div {
+            "screen": "bottom",
+            position: "absolute",
+            left: 0,
+            top: 0,
+            width: SCREEN_BOTTOM_WIDTH,
+            height: SCREEN_HEIGHT,
+            color: "main-text",
+            z_index: 2,
+            onkeypress: move |e: KeyboardEvent| {
+                if e.data().code() == Code::KeyB {
+                    on_close.call(());
+                }
+            },
+
+            div {
+                position: "absolute",
+                left: 8,
+                top: 4,
+                {tr("history.title")}
+            }
+            if records.is_empty() {
+                div {
+                    position: "absolute",
+                    left: 8,
+                    top: 4 + ROW_HEIGHT,
+                    color: "tips",
+                    {tr("history.empty")}
+                }
+            }
+            for (row, line) in lines {
+                div {
+                    position: "absolute",
+                    left: 8,
+                    top: 4 + ROW_HEIGHT * (row as i64 + 1),
+                    max_width: SCREEN_BOTTOM_WIDTH - 16,
+                    color: if row < totals_len { "main-text" } else { "tips" },
+                    "{line}"
+                }
+            }
+            div {
+                "scale": 0.5,
+                position: "absolute",
+                left: 4,
+                top: SCREEN_HEIGHT - 60,
+                PanelButton {
+                    label: tr("common.close"),
+                    value: "B",
+                    onclick: move |_| on_close.call(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(console: &str, duration_secs: u64) -> SessionRecord {
+        SessionRecord {
+            console: console.to_string(),
+            start: "2024-10-28 20.15.03.120".to_string(),
+            duration_secs,
+            modes: vec![CookMode::Solid, CookMode::ColorCycle],
+            brightness: [3, 0],
+            end: EndReason::HomeMenu,
+        }
+    }
+
+    #[test]
+    fn line_round_trip() {
+        let record = record("0123456789abcdef", 3600);
+        assert_eq!(SessionRecord::parse(&record.to_line()), Some(record));
+    }
+
+    #[test]
+    fn broken_lines_are_skipped() {
+        assert_eq!(SessionRecord::parse("console=1;start=x;duration=1"), None);
+        let line = record("1", 60).to_line().replace("home_menu", "lunch");
+        assert_eq!(SessionRecord::parse(&line), None);
+    }
+
+    #[test]
+    fn the_last_line_is_rewritten() {
+        // one file per test process, concurrent runs do not share it
+        let name = format!("cook_screen_history_test_{}.txt", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+        write_history(path, None, &record("a", 60)).unwrap();
+        let offset = write_history(path, None, &record("b", 600)).unwrap();
+        assert_eq!(
+            write_history(path, Some(offset), &record("b", 1200)).unwrap(),
+            offset
+        );
+        assert_eq!(
+            write_history(path, Some(offset), &record("b", 20)).unwrap(),
+            offset
+        );
+        assert_eq!(load_history(path), vec![record("a", 60), record("b", 20)]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn totals_per_console() {
+        let records = [record("b", 600), record("a", 3600), record("b", 1200)];
+        assert_eq!(
+            console_totals(&records),
+            vec![("a".to_string(), 3600, 1), ("b".to_string(), 1800, 2)]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+#![allow(non_snake_case)]
+
+use std::{sync::OnceLock, time::Duration};
+
+use dioxus::prelude::*;
+use tokio::{sync::watch, time::Instant};
+
+use crate::{constant::SCREEN_HEIGHT, i18n::tr_args, platform::KeyPad, utils::sleep_micros};
+
+use super::key_binding::combo_text;
+
+// how long the lock combo is held to unlock
+pub const UNLOCK_HOLD: Duration = Duration::from_secs(3);
+// how long the indicator stays after a blocked press
+const NOTICE_LENGTH: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LockState {
+    // held for UNLOCK_HOLD after locking to unlock again
+    pub combo: KeyPad,
+    pub locked_at: Instant,
+    // the lock itself or the last blocked press
+    pub notice_at: Instant,
+}
+
+static APP_LOCK: OnceLock<watch::Sender<Option<LockState>>> = OnceLock::new();
+
+// every input is ignored while locked, EventTrigger only watches for the unlock combo
+#[derive(Clone)]
+pub struct AppLock;
+
+impl AppLock {
+    // LockIndicator subscribes to it
+    pub fn get() -> &'static watch::Sender<Option<LockState>> {
+        APP_LOCK.get_or_init(|| watch::channel(None).0)
+    }
+
+    pub fn lock(combo: KeyPad) {
+        let now = Instant::now();
+        Self::get().send_replace(Some(LockState {
+            combo,
+            locked_at: now,
+            notice_at: now,
+        }));
+    }
+
+    pub fn unlock() {
+        Self::get().send_replace(None);
+    }
+
+    pub fn state() -> Option<LockState> {
+        *Self::get().borrow()
+    }
+
+    pub fn note_blocked() {
+        Self::get().send_if_modified(|lock| {
+            lock.as_mut()
+                .map(|lock| lock.notice_at = Instant::now())
+                .is_some()
+        });
+    }
+}
+
+// a short notice on the bottom screen when the lock starts or blocks a press
+#[component]
+pub fn LockIndicator() -> Element {
+    let mut combo = use_signal(|| None::<KeyPad>);
+
+    // only wakes up when the lock changes or the notice ends
+    use_future(move || async move {
+        let mut changes = AppLock::get().subscribe();
+        loop {
+            let lock = *changes.borrow_and_update();
+            let shown_for = lock
+                .map(|lock| NOTICE_LENGTH.saturating_sub(lock.notice_at.elapsed()))
+                .filter(|left| !left.is_zero());
+            let current = lock.filter(|_| shown_for.is_some()).map(|lock| lock.combo);
+            if *combo.peek() != current {
+                combo.set(current);
+            }
+            let changed = match shown_for {
+                Some(left) => tokio::select! {
+                    changed = changes.changed() => changed,
+                    _ = sleep_micros(left.as_micros() as u64) => Ok(()),
+                },
+                None => changes.changed().await,
+            };
+            if changed.is_err() {
+                break;
+            }
+        }
+    });
+
+    let current = combo()?;
+    let text = tr_args(
+        "lock.notice",
+        &[&combo_text(current), &UNLOCK_HOLD.as_secs()],
+    );
+
+    rsx! {
+        div {
+            "screen": "bottom",
+            "scale": 0.5,
+            position: "absolute",
+            left: 4,
+            top: SCREEN_HEIGHT - 20,
+            padding: 2,
+            color: "main-text",
+            background_color: "panel_bg",
+            z_index: 4,
+            "{text}"
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    platform::KeyPad,
+    render::revent::{KeyChange, BUTTONS},
+};
+
+// what the main screen does with a button combo
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Action {
+    Exit,
+    Help,
+    NextTimer,
+    ToggleCountdown,
+    NextMode,
+    Darker,
+    Brighter,
+    SelectTop,
+    SelectBottom,
+    // ignore every input until the combo is held again, see AppLock
+    Lock,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Exit,
+        Action::Help,
+        Action::NextTimer,
+        Action::ToggleCountdown,
+        Action::NextMode,
+        Action::Darker,
+        Action::Brighter,
+        Action::SelectTop,
+        Action::SelectBottom,
+        Action::Lock,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Exit => "exit",
+            Action::Help => "help",
+            Action::NextTimer => "timer",
+            Action::ToggleCountdown => "countdown",
+            Action::NextMode => "next_mode",
+            Action::Darker => "darker",
+            Action::Brighter => "brighter",
+            Action::SelectTop => "select_top",
+            Action::SelectBottom => "select_bottom",
+            Action::Lock => "lock",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|action| action.name() == text)
+            .copied()
+    }
+
+    fn default_combo(&self) -> KeyPad {
+        match self {
+            Action::Exit => KeyPad::START,
+            Action::Help => KeyPad::SELECT,
+            Action::NextTimer => KeyPad::X,
+            Action::ToggleCountdown => KeyPad::B,
+            Action::NextMode => KeyPad::Y,
+            Action::Darker => KeyPad::L,
+            Action::Brighter => KeyPad::R,
+            Action::SelectTop => KeyPad::ZL,
+            Action::SelectBottom => KeyPad::ZR,
+            Action::Lock => KeyPad::L | KeyPad::R,
+        }
+    }
+}
+
+// "L+R+Start", the names are the ones in BUTTONS
+pub fn parse_combo(text: &str) -> Option<KeyPad> {
+    text.split('+')
+        .map(|name| name.trim())
+        .map(|name| {
+            BUTTONS
+                .iter()
+                .find(|(_, button, _)| button.eq_ignore_ascii_case(name))
+                .map(|(button, ..)| *button)
+        })
+        .try_fold(KeyPad::empty(), |all, button| Some(all | button?))
+        .filter(|combo| !combo.is_empty())
+}
+
+pub fn combo_text(combo: KeyPad) -> String {
+    BUTTONS
+        .iter()
+        .filter(|(button, ..)| combo.contains(*button))
+        .map(|(_, name, _)| *name)
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+// the combo of every action, a combo has to be held exactly
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyBindings(BTreeMap<Action, KeyPad>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_combo()))
+                .collect(),
+        )
+    }
+}
+
+impl KeyBindings {
+    pub fn combo(&self, action: Action) -> KeyPad {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_combo())
+    }
+
+    pub fn set(&mut self, action: Action, combo: KeyPad) {
+        self.0.insert(action, combo);
+    }
+
+    // the first action bound to the held buttons
+    pub fn action(&self, held: KeyPad) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .find(|action| self.combo(**action) == held)
+            .copied()
+    }
+
+    // a longer combo starts with the held buttons
+    fn is_prefix(&self, held: KeyPad) -> bool {
+        Action::ALL
+            .iter()
+            .map(|action| self.combo(*action))
+            .any(|combo| combo != held && combo.contains(held))
+    }
+
+    // the first two actions sharing a combo
+    pub fn duplicate(&self) -> Option<(Action, Action)> {
+        Action::ALL.iter().enumerate().find_map(|(i, first)| {
+            Action::ALL[i + 1..]
+                .iter()
+                .find(|second| self.combo(*first) == self.combo(**second))
+                .map(|second| (*first, *second))
+        })
+    }
+}
+
+// the action of each key event, a combo acts on the press that completes it
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ActionResolver {
+    // the held buttons start a longer combo, their action waits for the release
+    pending: Option<Action>,
+}
+
+impl ActionResolver {
+    pub fn resolve(
+        &mut self,
+        keys: &KeyBindings,
+        held: KeyPad,
+        change: KeyChange,
+    ) -> Option<Action> {
+        match change {
+            KeyChange::Down(_) => {
+                let action = keys.action(held);
+                if keys.is_prefix(held) {
+                    self.pending = action;
+                    return None;
+                }
+                self.pending = None;
+                action
+            }
+            // nothing else was pressed since
+            KeyChange::Up(_) => self.pending.take(),
+            // holding a combo does not act again
+            KeyChange::Repeat => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combo_text_round_trip() {
+        let combo = KeyPad::L | KeyPad::R | KeyPad::START;
+        assert_eq!(combo_text(combo), "L+R+Start");
+        assert_eq!(parse_combo("l + r + start"), Some(combo));
+        assert_eq!(parse_combo("L+Home"), None);
+        assert_eq!(parse_combo(""), None);
+    }
+
+    #[test]
+    fn only_the_exact_combo_triggers() {
+        let mut keys = KeyBindings::default();
+        keys.set(Action::Exit, KeyPad::L | KeyPad::R | KeyPad::START);
+        assert_eq!(keys.action(KeyPad::START), None);
+        assert_eq!(keys.action(KeyPad::L), Some(Action::Darker));
+        assert_eq!(keys.action(KeyPad::L | KeyPad::R), Some(Action::Lock));
+        assert_eq!(
+            keys.action(KeyPad::L | KeyPad::R | KeyPad::START),
+            Some(Action::Exit)
+        );
+    }
+
+    #[test]
+    fn a_combo_acts_when_it_is_completed() {
+        let keys = KeyBindings::default();
+        let mut resolver = ActionResolver::default();
+        let lock = KeyPad::L | KeyPad::R;
+        assert_eq!(
+            resolver.resolve(&keys, KeyPad::L, KeyChange::Down(KeyPad::L)),
+            None
+        );
+        assert_eq!(
+            resolver.resolve(&keys, lock, KeyChange::Down(KeyPad::R)),
+            Some(Action::Lock)
+        );
+        assert_eq!(resolver.resolve(&keys, lock, KeyChange::Repeat), None);
+        assert_eq!(
+            resolver.resolve(&keys, KeyPad::L, KeyChange::Up(KeyPad::R)),
+            None
+        );
+        assert_eq!(
+            resolver.resolve(&keys, KeyPad::empty(), KeyChange::Up(KeyPad::L)),
+            None
+        );
+    }
+
+    #[test]
+    fn a_prefix_acts_on_release() {
+        let mut keys = KeyBindings::default();
+        keys.set(Action::Exit, KeyPad::L | KeyPad::R | KeyPad::START);
+        let mut resolver = ActionResolver::default();
+        assert_eq!(
+            resolver.resolve(&keys, KeyPad::L, KeyChange::Down(KeyPad::L)),
+            None
+        );
+        assert_eq!(resolver.resolve(&keys, KeyPad::L, KeyChange::Repeat), None);
+        assert_eq!(
+            resolver.resolve(&keys, KeyPad::empty(), KeyChange::Up(KeyPad::L)),
+            Some(Action::Darker)
+        );
+        let all = KeyPad::L | KeyPad::R | KeyPad::START;
+        for (held, pressed) in [(KeyPad::L, KeyPad::L), (KeyPad::L | KeyPad::R, KeyPad::R)] {
+            assert_eq!(
+                resolver.resolve(&keys, held, KeyChange::Down(pressed)),
+                None
+            );
+        }
+        assert_eq!(
+            resolver.resolve(&keys, all, KeyChange::Down(KeyPad::START)),
+            Some(Action::Exit)
+        );
+        for (held, released) in [
+            (KeyPad::L | KeyPad::R, KeyPad::START),
+            (KeyPad::L, KeyPad::R),
+            (KeyPad::empty(), KeyPad::L),
+        ] {
+            assert_eq!(resolver.resolve(&keys, held, KeyChange::Up(released)), None);
+        }
+    }
+
+    #[test]
+    fn duplicate_combos_are_found() {
+        let mut keys = KeyBindings::default();
+        assert_eq!(keys.duplicate(), None);
+        keys.set(Action::Help, KeyPad::X);
+        assert_eq!(keys.duplicate(), Some((Action::Help, Action::NextTimer)));
+    }
+}
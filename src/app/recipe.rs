@@ -0,0 +1,485 @@
+#![allow(non_snake_case)]
+
+use std::{collections::BTreeMap, fs, io, path::Path, time::Duration};
+
+use dioxus::prelude::*;
+use log::warn;
+use tokio::time::Instant;
+
+use crate::{
+    constant::RECIPES_DIR,
+    i18n::{tr, tr_args},
+    settings::{parse_bool, parse_screen_value, parse_secs, screen_lines},
+    utils::{format_duration, get_color_presets, sleep_micros},
+};
+
+use super::{cook_mode::CookMode, history::EndReason, screen_config::ScreenConfig, AppExit};
+
+// how long one step cooks
+pub const STEP_LENGTHS: [u64; 8] = [
+    60,
+    5 * 60,
+    10 * 60,
+    30 * 60,
+    3600,
+    2 * 3600,
+    4 * 3600,
+    8 * 3600,
+];
+
+// 600 -> "10m", 7200 -> "2h"
+pub fn length_label(secs: u64) -> String {
+    if secs.is_multiple_of(3600) {
+        tr_args("time.hours", &[&(secs / 3600)])
+    } else {
+        tr_args("time.minutes", &[&(secs / 60)])
+    }
+}
+
+// one step shows the same config on both screens
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecipeStep {
+    pub config: ScreenConfig,
+    pub length_secs: u64,
+}
+
+impl Default for RecipeStep {
+    fn default() -> Self {
+        Self {
+            config: ScreenConfig::default(),
+            length_secs: 10 * 60,
+        }
+    }
+}
+
+impl RecipeStep {
+    // "color_cycle red,green,blue 10m"
+    pub fn label(&self) -> String {
+        let config = &self.config;
+        let detail = match config.mode {
+            CookMode::Solid => config.solid_color.label(),
+            CookMode::ColorCycle => config
+                .cycle
+                .colors
+                .iter()
+                .map(|color| color.label())
+                .collect::<Vec<_>>()
+                .join(","),
+            CookMode::TestPattern => config.pattern.label(),
+            CookMode::BurnIn => config.burn_in.image.clone().unwrap_or_default(),
+            CookMode::Stereo3d => config.stereo.label(),
+            CookMode::Exerciser => String::new(),
+        };
+        format!(
+            "{} {} {}",
+            config.mode.label(),
+            detail,
+            length_label(self.length_secs)
+        )
+    }
+
+    // the next length, back to the shortest after the longest
+    pub fn next_length(&mut self) {
+        let idx = STEP_LENGTHS
+            .iter()
+            .position(|&secs| secs > self.length_secs)
+            .unwrap_or(0);
+        self.length_secs = STEP_LENGTHS[idx];
+    }
+}
+
+/// steps cooked one after another, saved as `RECIPES_DIR/<name>.txt`
+///
+/// ```text
+/// exit_at_end=true
+/// step.1.mode=solid
+/// step.1.solid_color=white
+/// step.1.length=1800
+/// step.2.mode=color_cycle
+/// step.2.cycle_colors=red,green,blue
+/// step.2.length=600
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Recipe {
+    // the file name without .txt, empty until it is saved
+    pub name: String,
+    // never empty
+    pub steps: Vec<RecipeStep>,
+    // exit the app after the last step, otherwise the last step keeps cooking
+    pub exit_at_end: bool,
+}
+
+impl Default for Recipe {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            steps: vec![RecipeStep::default()],
+            exit_at_end: true,
+        }
+    }
+}
+
+fn recipe_path(name: &str) -> String {
+    format!("{}/{}.txt", RECIPES_DIR, name)
+}
+
+// names of the saved recipes, sorted
+pub fn list_recipes() -> Vec<String> {
+    let mut names = fs::read_dir(RECIPES_DIR)
+        .map(|dir| {
+            dir.filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter_map(|name| name.strip_suffix(".txt").map(|name| name.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+impl Recipe {
+    // invalid lines are skipped like in the settings file
+    pub fn parse(name: &str, text: &str) -> Self {
+        let mut recipe = Self {
+            name: name.to_string(),
+            steps: vec![],
+            ..Default::default()
+        };
+        // steps by their number, gaps are fine
+        let mut steps = BTreeMap::new();
+        let presets = get_color_presets();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let valid = match key.split('.').collect::<Vec<_>>()[..] {
+                ["exit_at_end"] => parse_bool(value).map(|exit| recipe.exit_at_end = exit),
+                ["step", number, key] => number.parse::<u32>().ok().and_then(|number| {
+                    let step: &mut RecipeStep = steps.entry(number).or_default();
+                    match key {
+                        "length" => parse_secs(value)
+                            .filter(|secs| *secs > 0)
+                            .map(|secs| step.length_secs = secs),
+                        _ => parse_screen_value(&mut step.config, key, value, &presets),
+                    }
+                }),
+                _ => Some(()),
+            }
+            .is_some();
+            if !valid {
+                warn!("invalid recipe value {}={}", key, value);
+            }
+        }
+        recipe.steps = steps.into_values().collect();
+        if recipe.steps.is_empty() {
+            recipe.steps.push(RecipeStep::default());
+        }
+        recipe
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("exit_at_end={}", self.exit_at_end)];
+        for (idx, step) in self.steps.iter().enumerate() {
+            let prefix = format!("step.{}", idx + 1);
+            // colors are saved as values, a recipe outlives the presets
+            lines.extend(screen_lines(&prefix, &step.config, &BTreeMap::new()));
+            lines.push(format!("{}.length={}", prefix, step.length_secs));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    pub fn load(name: &str) -> io::Result<Self> {
+        fs::read_to_string(recipe_path(name)).map(|text| Self::parse(name, &text))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = recipe_path(&self.name);
+        if let Some(dir) = Path::new(&path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    pub fn total_secs(&self) -> u64 {
+        self.steps.iter().map(|step| step.length_secs).sum()
+    }
+
+    // a copy of the step after it, returns the index of the copy
+    pub fn add_step(&mut self, idx: usize) -> usize {
+        let idx = idx.min(self.steps.len() - 1);
+        self.steps.insert(idx + 1, self.steps[idx].clone());
+        idx + 1
+    }
+
+    // the last step is kept, returns the index to select afterwards
+    pub fn remove_step(&mut self, idx: usize) -> usize {
+        if self.steps.len() > 1 && idx < self.steps.len() {
+            self.steps.remove(idx);
+        }
+        idx.min(self.steps.len() - 1)
+    }
+}
+
+// a recipe that is cooking
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecipeRun {
+    pub recipe: Recipe,
+    pub step: usize,
+    // time spent in the step before the last pause
+    elapsed: Duration,
+    // None while paused
+    resumed_at: Option<Instant>,
+}
+
+impl RecipeRun {
+    pub fn new(recipe: Recipe, now: Instant) -> Self {
+        Self {
+            recipe,
+            step: 0,
+            elapsed: Duration::ZERO,
+            resumed_at: Some(now),
+        }
+    }
+
+    pub fn current(&self) -> &RecipeStep {
+        &self.recipe.steps[self.step]
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.resumed_at.is_none()
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        self.elapsed
+            + self
+                .resumed_at
+                .map(|at| now.saturating_duration_since(at))
+                .unwrap_or_default()
+    }
+
+    fn left(&self, now: Instant) -> Duration {
+        Duration::from_secs(self.current().length_secs).saturating_sub(self.elapsed(now))
+    }
+
+    pub fn remaining_secs(&self, now: Instant) -> u64 {
+        let left = self.left(now);
+        // round up, so 00:00:00 is only shown when the step is over
+        left.as_secs() + (left.subsec_nanos() > 0) as u64
+    }
+
+    // time until the remaining seconds change
+    pub fn until_next_second(&self, now: Instant) -> Duration {
+        match self.left(now).subsec_nanos() {
+            0 => Duration::from_secs(1),
+            nanos => Duration::from_nanos(nanos as u64),
+        }
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        match self.resumed_at {
+            Some(_) => {
+                self.elapsed = self.elapsed(now);
+                self.resumed_at = None;
+            }
+            None => self.resumed_at = Some(now),
+        }
+    }
+
+    // move on to the step cooking at `now`, true once the last step is over
+    pub fn advance(&mut self, now: Instant) -> bool {
+        let mut elapsed = self.elapsed(now);
+        while self.step + 1 < self.recipe.steps.len() {
+            let length = Duration::from_secs(self.current().length_secs);
+            if elapsed < length {
+                break;
+            }
+            elapsed -= length;
+            self.step += 1;
+        }
+        self.elapsed = elapsed;
+        self.resumed_at = self.resumed_at.map(|_| now);
+        elapsed >= Duration::from_secs(self.current().length_secs)
+    }
+}
+
+// the step and its remaining time, A or a tap pauses, B stops the recipe
+#[component]
+pub fn RecipeStatus(mut run: Signal<Option<RecipeRun>>) -> Element {
+    let mut remaining = use_signal(|| 0_u64);
+    let mut done = use_signal(|| false);
+
+    use_future(move || async move {
+        loop {
+            let now = Instant::now();
+            let Some(mut next) = run.peek().clone() else {
+                sleep_micros(1_000_000).await;
+                continue;
+            };
+            let is_done = next.advance(now);
+            if is_done && next.recipe.exit_at_end {
+                AppExit::set_exit(EndReason::Recipe);
+            }
+            let left = next.remaining_secs(now);
+            let wait = next.until_next_second(now);
+            // the screens only rerender when the step changes
+            if next.step != run.peek().as_ref().map_or(0, |run| run.step) {
+                run.set(Some(next));
+            }
+            if *remaining.peek() != left {
+                remaining.set(left);
+            }
+            if *done.peek() != is_done {
+                done.set(is_done);
+            }
+            sleep_micros(wait.as_micros() as u64).await;
+        }
+    });
+
+    let mut toggle_pause = move || {
+        let mut next = run.peek().clone();
+        if let Some(next) = next.as_mut() {
+            next.toggle_pause(Instant::now());
+        }
+        run.set(next);
+    };
+
+    let current = run()?;
+    let name = match current.recipe.name.as_str() {
+        "" => tr("recipe.unnamed"),
+        name => name,
+    };
+    let progress = format!("{}/{}", current.step + 1, current.recipe.steps.len());
+    let label = current.current().label();
+    let state = if done() {
+        tr("recipe.done").to_string()
+    } else if current.is_paused() {
+        tr_args("recipe.paused", &[&format_duration(remaining())])
+    } else {
+        format_duration(remaining())
+    };
+
+    rsx! {
+        div {
+            "screen": "bottom",
+            "scale": 0.5,
+            position: "absolute",
+            left: 4,
+            top: 24,
+            padding: 2,
+            color: "main-text",
+            background_color: "panel_bg",
+            z_index: 1,
+            onkeypress: move |e: KeyboardEvent| match e.data().code() {
+                // holding A pauses or resumes once
+                _ if e.data().is_auto_repeating() => {}
+                Code::KeyA => toggle_pause(),
+                Code::KeyB => run.set(None),
+                _ => {}
+            },
+            onclick: move |_| toggle_pause(),
+            div { "{name} {progress}" }
+            div { "{label}" }
+            div { "{state}" }
+            div { color: "tips", {tr("recipe.status_tips")} }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::cook_color::CookColor;
+
+    fn recipe() -> Recipe {
+        let mut cycle = RecipeStep::default();
+        cycle.config.mode = CookMode::ColorCycle;
+        cycle.config.cycle.colors = vec![CookColor::Red, CookColor::Green, CookColor::Blue];
+        let mut black = RecipeStep::default();
+        black.config.solid_color = CookColor::Black;
+        Recipe {
+            name: "burn out".to_string(),
+            steps: vec![
+                RecipeStep {
+                    config: ScreenConfig {
+                        solid_color: CookColor::White,
+                        ..Default::default()
+                    },
+                    length_secs: 30 * 60,
+                },
+                cycle,
+                RecipeStep {
+                    length_secs: 5 * 60,
+                    ..black
+                },
+            ],
+            exit_at_end: true,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let recipe = recipe();
+        assert_eq!(Recipe::parse("burn out", &recipe.to_text()), recipe);
+    }
+
+    #[test]
+    fn steps_are_ordered_by_number() {
+        let recipe = Recipe::parse(
+            "",
+            "step.10.solid_color=red\nstep.2.length=60\nstep.x.length=60\nexit_at_end=false\n",
+        );
+        assert_eq!(recipe.steps.len(), 2);
+        assert_eq!(recipe.steps[0].length_secs, 60);
+        assert_eq!(recipe.steps[1].config.solid_color, CookColor::Red);
+        assert!(!recipe.exit_at_end);
+        assert_eq!(Recipe::parse("", "").steps.len(), 1);
+    }
+
+    #[test]
+    fn run_moves_through_the_steps() {
+        let start = Instant::now();
+        let mut run = RecipeRun::new(recipe(), start);
+        assert!(!run.advance(start + Duration::from_secs(60)));
+        assert_eq!(
+            (
+                run.step,
+                run.remaining_secs(start + Duration::from_secs(60))
+            ),
+            (0, 29 * 60)
+        );
+        // skips the whole cycle step when nothing woke up in between
+        let late = start + Duration::from_secs(41 * 60);
+        assert!(!run.advance(late));
+        assert_eq!((run.step, run.remaining_secs(late)), (2, 4 * 60));
+        assert!(run.advance(start + Duration::from_secs(45 * 60)));
+    }
+
+    #[test]
+    fn pause_stops_the_clock() {
+        let start = Instant::now();
+        let mut run = RecipeRun::new(recipe(), start);
+        run.toggle_pause(start + Duration::from_secs(60));
+        let later = start + Duration::from_secs(3600);
+        assert!(!run.advance(later));
+        assert_eq!((run.step, run.remaining_secs(later)), (0, 29 * 60));
+        run.toggle_pause(later);
+        run.advance(later + Duration::from_secs(60));
+        assert_eq!(run.remaining_secs(later + Duration::from_secs(60)), 28 * 60);
+    }
+
+    #[test]
+    fn the_last_step_is_kept() {
+        let mut recipe = recipe();
+        assert_eq!(recipe.add_step(5), 3);
+        assert_eq!(recipe.remove_step(3), 2);
+        for _ in 0..5 {
+            recipe.remove_step(0);
+        }
+        assert_eq!(recipe.steps.len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+#![allow(non_snake_case)]
+
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use log::{error, warn};
+
+use crate::{
+    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT},
+    i18n::tr,
+    platform::pl_show_swkbd,
+    resource::Resource,
+    utils::{color_name_rgba, format_duration},
+};
+
+use super::{
+    control_panel::PanelButton,
+    recipe::{list_recipes, Recipe},
+    screen_config::CookScreen,
+    screen_fill::ScreenFill,
+};
+
+const ROW_HEIGHT: i64 = 16;
+// steps shown around the selected one
+const VISIBLE_STEPS: usize = 6;
+const BUTTONS_TOP: i64 = SCREEN_HEIGHT - 2 * 56 - 4;
+
+// steps on the bottom screen, the top screen previews the selected step
+#[component]
+pub fn RecipeEditor(on_run: EventHandler<Recipe>, on_close: EventHandler<()>) -> Element {
+    let resource = use_context::<Rc<Resource>>();
+    // start with the first saved recipe
+    let mut recipe = use_signal(|| {
+        list_recipes()
+            .first()
+            .and_then(|name| Recipe::load(name).ok())
+            .unwrap_or_default()
+    });
+    let mut selected = use_signal(|| 0_usize);
+
+    let current = recipe();
+    let idx = selected().min(current.steps.len() - 1);
+    let first = idx.saturating_sub(VISIBLE_STEPS / 2);
+    let name = match current.name.as_str() {
+        "" => tr("recipe.new"),
+        name => name,
+    };
+    let exit_at_end = current.exit_at_end;
+    let rows = current
+        .steps
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(VISIBLE_STEPS)
+        .map(|(row, step)| (row, format!("{}. {}", row + 1, step.label())))
+        .collect::<Vec<_>>();
+
+    rsx! {
+        CookScreen { screen: "top", config: current.steps[idx].config.clone() }
+        ScreenFill { screen: "bottom", color: color_name_rgba("main_bg") }
+        div {
+            "screen": "bottom",
+            position: "absolute",
+            left: 0,
+            top: 0,
+            width: SCREEN_BOTTOM_WIDTH,
+            height: SCREEN_HEIGHT,
+            color: "main-text",
+            z_index: 2,
+            onkeypress: move |e: KeyboardEvent| {
+                let code = e.data().code();
+                let idx = *selected.peek();
+                match code {
+                    Code::KeyB => on_close.call(()),
+                    // pick the step
+                    Code::KeyL => selected.set(idx.saturating_sub(1)),
+                    Code::KeyR => {
+                        let last = recipe.peek().steps.len() - 1;
+                        selected.set((idx + 1).min(last));
+                    }
+                    Code::KeyY => recipe.write().steps[idx].config.next_mode(),
+                    Code::KeyX => recipe.write().steps[idx].next_length(),
+                    // the d-pad and A set up the mode like on the main screen
+                    _ => {
+                        let mut next = recipe.peek().steps[idx].clone();
+                        next.config.handle_key("top", code, e.data().is_auto_repeating());
+                        if next != recipe.peek().steps[idx] {
+                            recipe.write().steps[idx] = next;
+                        }
+                    }
+                }
+            },
+
+            div {
+                position: "absolute",
+                left: 8,
+                top: 4,
+                "{name} {format_duration(current.total_secs())}"
+            }
+            for (row, label) in rows {
+                div {
+                    position: "absolute",
+                    left: 8,
+                    top: 8 + ROW_HEIGHT * (row - first + 1) as i64,
+                    max_width: SCREEN_BOTTOM_WIDTH - 16,
+                    color: if row == idx { "main-text" } else { "tips" },
+                    background_color: if row == idx { "selected_bg" } else { "main_bg" },
+                    onclick: move |_| selected.set(row),
+                    "{label}"
+                }
+            }
+            div {
+                position: "absolute",
+                left: 8,
+                top: BUTTONS_TOP - ROW_HEIGHT,
+                color: "tips",
+                {tr("recipe.editor_tips")}
+            }
+            div {
+                "scale": 0.5,
+                display: "flex",
+                flex_wrap: "wrap",
+                position: "absolute",
+                left: 4,
+                top: BUTTONS_TOP,
+                width: SCREEN_BOTTOM_WIDTH - 8,
+                PanelButton {
+                    label: tr("recipe.add"),
+                    value: tr("recipe.step"),
+                    onclick: move |_| {
+                        let next = recipe.write().add_step(*selected.peek());
+                        selected.set(next);
+                    },
+                }
+                PanelButton {
+                    label: tr("recipe.remove"),
+                    value: tr("recipe.step"),
+                    onclick: move |_| {
+                        let next = recipe.write().remove_step(*selected.peek());
+                        selected.set(next);
+                    },
+                }
+                PanelButton {
+                    label: tr("recipe.at_end"),
+                    value: tr(if exit_at_end { "recipe.exit" } else { "recipe.keep" }),
+                    onclick: move |_| {
+                        let exit = !recipe.peek().exit_at_end;
+                        recipe.write().exit_at_end = exit;
+                    },
+                }
+                PanelButton {
+                    label: tr("recipe.load"),
+                    value: tr("recipe.next"),
+                    onclick: move |_| {
+                        // the saved recipe after the current one
+                        let names = list_recipes();
+                        let current = recipe.peek().name.clone();
+                        let next = names
+                            .iter()
+                            .find(|name| **name > current)
+                            .or(names.first());
+                        let Some(next) = next else {
+                            warn!("no saved recipes");
+                            return;
+                        };
+                        match Recipe::load(next) {
+                            Ok(loaded) => {
+                                recipe.set(loaded);
+                                selected.set(0);
+                            }
+                            Err(err) => error!("failed to load recipe {}: {}", next, err),
+                        }
+                    },
+                }
+                PanelButton {
+                    label: tr("common.save"),
+                    value: tr("recipe.save_as"),
+                    onclick: move |_| {
+                        let initial = recipe.peek().name.clone();
+                        let Some(name) = pl_show_swkbd(&resource, &initial) else {
+                            return;
+                        };
+                        let mut next = recipe.peek().clone();
+                        next.name = name.trim().to_string();
+                        if next.name.is_empty() {
+                            return;
+                        }
+                        if let Err(err) = next.save() {
+                            error!("failed to save recipe {}: {}", next.name, err);
+                        }
+                        recipe.set(next);
+                    },
+                }
+                PanelButton {
+                    label: tr("recipe.run"),
+                    value: format_duration(current.total_secs()),
+                    onclick: move |_| on_run.call(recipe.peek().clone()),
+                }
+            }
+        }
+    }
+}
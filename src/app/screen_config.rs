@@ -0,0 +1,166 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::platform::MAX_BACKLIGHT_LEVEL;
+
+use super::{
+    burn_in::{BurnIn, BurnInFill},
+    color_cycle::{ColorCycle, ColorCycleFill},
+    cook_color::CookColor,
+    cook_mode::CookMode,
+    exerciser::{Exerciser, ExerciserFill},
+    screen_fill::ScreenFill,
+    stereo_3d::{Stereo3dFill, Stereo3dPattern},
+    test_pattern::{TestPattern, TestPatternFill},
+};
+
+// what one screen shows, the top and bottom screen each have their own
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ScreenConfig {
+    pub mode: CookMode,
+    pub solid_color: CookColor,
+    pub cycle: ColorCycle,
+    pub exerciser: Exerciser,
+    pub pattern: TestPattern,
+    pub burn_in: BurnIn,
+    pub stereo: Stereo3dPattern,
+    // backlight level, 0 keeps the brightness from before the app started
+    pub brightness: u8,
+}
+
+impl ScreenConfig {
+    pub fn next_mode(&mut self) {
+        self.mode = self.mode.next();
+    }
+
+    pub fn brighter(&mut self) {
+        self.brightness = (self.brightness + 1).min(MAX_BACKLIGHT_LEVEL);
+    }
+
+    pub fn darker(&mut self) {
+        self.brightness = self.brightness.saturating_sub(1);
+    }
+
+    // the d-pad and A depend on the mode, a held button repeats the steps but not the toggles
+    pub fn handle_key(&mut self, screen: &str, code: Code, is_repeat: bool) {
+        match self.mode {
+            CookMode::Solid => match code {
+                // pick the solid color
+                Code::ArrowLeft => self.solid_color = self.solid_color.step(false),
+                Code::ArrowRight => self.solid_color = self.solid_color.step(true),
+                // add or remove the solid color in the cycle
+                Code::KeyA if !is_repeat => self.cycle.toggle_color(self.solid_color),
+                _ => {}
+            },
+            CookMode::ColorCycle => match code {
+                // cycle speed
+                Code::ArrowUp => self.cycle.longer_interval(),
+                Code::ArrowDown => self.cycle.shorter_interval(),
+                Code::KeyA if !is_repeat => self.cycle.toggle_fade(),
+                _ => {}
+            },
+            CookMode::Exerciser => {
+                let has_patch = self.exerciser.patch.is_some();
+                match code {
+                    Code::KeyA if !is_repeat => self.exerciser.next_patch(screen),
+                    Code::ArrowLeft if has_patch => self.exerciser.move_patch(screen, -1, 0),
+                    Code::ArrowRight if has_patch => self.exerciser.move_patch(screen, 1, 0),
+                    Code::ArrowUp if has_patch => self.exerciser.move_patch(screen, 0, -1),
+                    Code::ArrowDown if has_patch => self.exerciser.move_patch(screen, 0, 1),
+                    Code::ArrowLeft | Code::ArrowRight | Code::ArrowUp | Code::ArrowDown
+                        if !is_repeat =>
+                    {
+                        self.exerciser.toggle_style()
+                    }
+                    _ => {}
+                }
+            }
+            CookMode::TestPattern => match code {
+                Code::ArrowLeft | Code::ArrowUp => self.pattern = self.pattern.step(false),
+                Code::ArrowRight | Code::ArrowDown => self.pattern = self.pattern.step(true),
+                _ => {}
+            },
+            CookMode::BurnIn => match code {
+                // pick the image
+                Code::ArrowLeft => self.burn_in.step_image(false),
+                Code::ArrowRight => self.burn_in.step_image(true),
+                Code::ArrowUp => self.burn_in.longer(),
+                Code::ArrowDown => self.burn_in.shorter(),
+                Code::KeyA if !is_repeat => self.burn_in.toggle_preview(),
+                _ => {}
+            },
+            CookMode::Stereo3d => match code {
+                Code::ArrowLeft | Code::ArrowUp => self.stereo = self.stereo.step(false),
+                Code::ArrowRight | Code::ArrowDown => self.stereo = self.stereo.step(true),
+                _ => {}
+            },
+        }
+    }
+}
+
+#[component]
+pub fn CookScreen(screen: &'static str, config: ReadOnlySignal<ScreenConfig>) -> Element {
+    let ScreenConfig {
+        mode,
+        solid_color,
+        cycle,
+        exerciser,
+        pattern,
+        burn_in,
+        stereo,
+        ..
+    } = config();
+
+    match mode {
+        CookMode::Solid => rsx! {
+            ScreenFill { screen, color: solid_color.rgba() }
+        },
+        CookMode::ColorCycle => rsx! {
+            ColorCycleFill { screen, cycle }
+        },
+        CookMode::Exerciser => rsx! {
+            ExerciserFill { screen, exerciser }
+        },
+        CookMode::TestPattern => rsx! {
+            TestPatternFill { screen, pattern }
+        },
+        CookMode::BurnIn => rsx! {
+            BurnInFill { screen, burn_in }
+        },
+        CookMode::Stereo3d => rsx! {
+            Stereo3dFill { screen, pattern: stereo }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_buttons_repeat_steps_but_not_toggles() {
+        let mut config = ScreenConfig::default();
+        let colors = config.cycle.colors.clone();
+        config.handle_key("top", Code::KeyA, false);
+        assert_ne!(config.cycle.colors, colors);
+        let toggled = config.cycle.colors.clone();
+        config.handle_key("top", Code::KeyA, true);
+        assert_eq!(config.cycle.colors, toggled);
+
+        let color = config.solid_color;
+        config.handle_key("top", Code::ArrowRight, true);
+        assert_ne!(config.solid_color, color);
+
+        config.mode = CookMode::Exerciser;
+        let exerciser = config.exerciser;
+        config.handle_key("top", Code::KeyA, true);
+        config.handle_key("top", Code::ArrowLeft, true);
+        assert_eq!(config.exerciser, exerciser);
+
+        config.mode = CookMode::BurnIn;
+        let burn_in = config.burn_in.clone();
+        config.handle_key("top", Code::KeyA, true);
+        assert_eq!(config.burn_in, burn_in);
+    }
+}
@@ -0,0 +1,30 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT, SCREEN_TOP_WIDTH};
+
+pub fn screen_width(screen: &str) -> i64 {
+    if screen == "bottom" {
+        SCREEN_BOTTOM_WIDTH
+    } else {
+        SCREEN_TOP_WIDTH
+    }
+}
+
+// clear the screen and fill it with one color
+#[component]
+pub fn ScreenFill(screen: &'static str, color: u32) -> Element {
+    rsx! {
+        div {
+            "screen": screen,
+            "bg_reset": color as i64,
+            position: "absolute",
+            left: 0,
+            top: 0,
+            width: screen_width(screen),
+            height: SCREEN_HEIGHT,
+            background_color: color as i64,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+#![allow(non_snake_case)]
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use tokio::time::Instant;
+
+use crate::{
+    i18n::tr,
+    utils::{format_duration, sleep_micros},
+};
+
+use super::{history::EndReason, recipe::length_label, AppExit};
+
+// 0 means no time limit
+pub const SESSION_LENGTHS: [u64; 7] = [0, 10 * 60, 30 * 60, 3600, 2 * 3600, 4 * 3600, 8 * 3600];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SessionTimer {
+    pub length_secs: u64,
+    pub show_countdown: bool,
+}
+
+impl Default for SessionTimer {
+    fn default() -> Self {
+        Self {
+            length_secs: 0,
+            show_countdown: true,
+        }
+    }
+}
+
+impl SessionTimer {
+    pub fn next_length(&mut self) {
+        let idx = SESSION_LENGTHS
+            .iter()
+            .position(|&secs| secs == self.length_secs)
+            .unwrap_or(0);
+        self.length_secs = SESSION_LENGTHS[(idx + 1) % SESSION_LENGTHS.len()];
+    }
+
+    pub fn length_label(&self) -> String {
+        match self.length_secs {
+            0 => tr("common.off").to_string(),
+            secs => length_label(secs),
+        }
+    }
+
+    pub fn toggle_countdown(&mut self) {
+        self.show_countdown = !self.show_countdown;
+    }
+}
+
+// exit the app when the session is over, restart the countdown when the length changes
+#[component]
+pub fn SessionCountdown(timer: Signal<SessionTimer>) -> Element {
+    let mut remaining = use_signal(|| timer.peek().length_secs);
+
+    use_future(move || async move {
+        let mut length = 0;
+        let mut started_at = Instant::now();
+        loop {
+            let current = timer.peek().length_secs;
+            if current != length {
+                length = current;
+                started_at = Instant::now();
+            }
+            let elapsed = started_at.elapsed();
+            if length > 0 {
+                let left = length.saturating_sub(elapsed.as_secs());
+                if left == 0 {
+                    AppExit::set_exit(EndReason::Timer);
+                }
+                if *remaining.peek() != left {
+                    remaining.set(left);
+                }
+            }
+            // wake up when the next second starts
+            let wait = Duration::from_secs(1) - Duration::from_nanos(elapsed.subsec_nanos() as u64);
+            sleep_micros(wait.as_micros() as u64).await;
+        }
+    });
+
+    let SessionTimer {
+        length_secs,
+        show_countdown,
+    } = timer();
+
+    rsx! {
+        if show_countdown && length_secs > 0 {
+            div {
+                "screen": "bottom",
+                "scale": 0.5,
+                position: "absolute",
+                left: 4,
+                top: 4,
+                padding: 2,
+                color: "main-text",
+                background_color: "panel_bg",
+                z_index: 1,
+                "{format_duration(remaining())}"
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::{
+    color::rgba,
+    constant::{MAX_DEEP_3D, SCREEN_HEIGHT, SCREEN_TOP_WIDTH},
+    i18n::{tr, tr_args, tr_name},
+};
+
+use super::{
+    cook_color::CookColor,
+    screen_fill::{screen_width, ScreenFill},
+    App3d,
+};
+
+// depth of each layer, negative is in front of the screen
+const LAYER_DEPTHS: [f32; 5] = [
+    -MAX_DEEP_3D,
+    -MAX_DEEP_3D / 2.0,
+    0.0,
+    MAX_DEEP_3D / 2.0,
+    MAX_DEEP_3D,
+];
+
+// calibration patterns for the parallax barrier, only the top screen is 3d
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Stereo3dPattern {
+    // a grid band at every layer depth
+    #[default]
+    DepthGrid,
+    // crosshairs in front of, on and behind the screen
+    Crosshair,
+    // a checkerboard in only one eye, the other eye should stay black
+    Crosstalk,
+}
+
+impl Stereo3dPattern {
+    pub const ALL: [Stereo3dPattern; 3] = [
+        Stereo3dPattern::DepthGrid,
+        Stereo3dPattern::Crosshair,
+        Stereo3dPattern::Crosstalk,
+    ];
+
+    pub fn step(&self, forward: bool) -> Self {
+        let len = Self::ALL.len();
+        let idx = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        if forward {
+            Self::ALL[(idx + 1) % len]
+        } else {
+            Self::ALL[(idx + len - 1) % len]
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stereo3dPattern::DepthGrid => "depth_grid",
+            Stereo3dPattern::Crosshair => "crosshair",
+            Stereo3dPattern::Crosstalk => "crosstalk",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr_name("stereo_pattern", self.name())
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|pattern| pattern.name() == text)
+            .copied()
+    }
+}
+
+// pixels between the left and the right image of a layer, each eye moves by depth * slider
+pub fn disparity(depth: f32, slider: f32) -> f32 {
+    2.0 * depth * slider
+}
+
+// the slider as the renderer sees it, 0 when 3d is off in the settings
+fn use_slider_3d() -> Signal<f32> {
+    let mut slider = use_signal(|| *App3d::watch_slider().borrow());
+    use_future(move || async move {
+        let mut changes = App3d::watch_slider();
+        // only wakes up when the slider moves
+        while changes.changed().await.is_ok() {
+            let current = *changes.borrow_and_update();
+            slider.set(current);
+        }
+    });
+    slider
+}
+
+// warm in front of the screen, white on it, cold behind it
+fn layer_color(idx: usize) -> u32 {
+    [
+        rgba(0xff, 0x40, 0x40, 0xff),
+        rgba(0xff, 0xff, 0x40, 0xff),
+        rgba(0xff, 0xff, 0xff, 0xff),
+        rgba(0x40, 0xff, 0xff, 0xff),
+        rgba(0x40, 0x80, 0xff, 0xff),
+    ][idx % LAYER_DEPTHS.len()]
+}
+
+#[component]
+fn TopPattern(pattern: Stereo3dPattern) -> Element {
+    let band_height = SCREEN_HEIGHT / LAYER_DEPTHS.len() as i64;
+    let column_width = SCREEN_TOP_WIDTH / 3;
+    let half_width = SCREEN_TOP_WIDTH / 2;
+    let eye_labels = [
+        ("left", tr("stereo.left_only")),
+        ("right", tr("stereo.right_only")),
+    ];
+
+    match pattern {
+        Stereo3dPattern::DepthGrid => rsx! {
+            for (idx, depth) in LAYER_DEPTHS.into_iter().enumerate() {
+                div {
+                    "screen": "top",
+                    "deep_3d": depth as f64,
+                    position: "absolute",
+                    left: 0,
+                    top: band_height * idx as i64,
+                    width: SCREEN_TOP_WIDTH,
+                    height: band_height,
+                    canvas {
+                        "pattern": "grid",
+                        "spacing": 10,
+                        "fg": layer_color(idx) as i64,
+                        width: SCREEN_TOP_WIDTH,
+                        height: band_height,
+                    }
+                    div {
+                        position: "absolute",
+                        left: 4,
+                        top: 2,
+                        color: "main-text",
+                        background_color: "panel_bg",
+                        "{depth}"
+                    }
+                }
+            }
+        },
+        Stereo3dPattern::Crosshair => rsx! {
+            for (idx, depth) in [LAYER_DEPTHS[0], 0.0, LAYER_DEPTHS[4]].into_iter().enumerate() {
+                canvas {
+                    "screen": "top",
+                    "deep_3d": depth as f64,
+                    "pattern": "crosshair",
+                    "fg": layer_color(idx * 2) as i64,
+                    position: "absolute",
+                    left: column_width * idx as i64,
+                    top: 0,
+                    width: column_width,
+                    height: SCREEN_HEIGHT,
+                }
+            }
+        },
+        Stereo3dPattern::Crosstalk => rsx! {
+            for (idx, (eye, label)) in eye_labels.into_iter().enumerate() {
+                div {
+                    "screen": "top",
+                    "eye": eye,
+                    position: "absolute",
+                    left: half_width * idx as i64,
+                    top: 0,
+                    width: half_width,
+                    height: SCREEN_HEIGHT,
+                    canvas {
+                        "pattern": "checkerboard",
+                        "cell": 16,
+                        "fg": CookColor::White.rgba() as i64,
+                        width: half_width,
+                        height: SCREEN_HEIGHT,
+                    }
+                    div {
+                        position: "absolute",
+                        left: 4,
+                        top: 4,
+                        color: "main-text",
+                        background_color: "panel_bg",
+                        "{label}"
+                    }
+                }
+            }
+        },
+    }
+}
+
+// the pattern on the top screen, the slider and the layer disparities on the bottom
+#[component]
+pub fn Stereo3dFill(screen: &'static str, pattern: Stereo3dPattern) -> Element {
+    let slider = use_slider_3d();
+
+    if screen == "top" {
+        return rsx! {
+            ScreenFill { screen, color: CookColor::Black.rgba() }
+            TopPattern { pattern }
+        };
+    }
+
+    let title = tr_args(
+        "stereo.title",
+        &[&pattern.label(), &format!("{:.2}", slider())],
+    );
+    let rows = LAYER_DEPTHS
+        .into_iter()
+        .map(|depth| {
+            let px = format!("{:.1}", disparity(depth, slider()));
+            tr_args("stereo.depth", &[&depth, &px])
+        })
+        .collect::<Vec<_>>();
+
+    rsx! {
+        ScreenFill { screen, color: CookColor::Black.rgba() }
+        div {
+            "screen": screen,
+            position: "absolute",
+            left: 8,
+            top: 8,
+            max_width: screen_width(screen) - 16,
+            color: "main-text",
+            div { "{title}" }
+            for row in rows {
+                div { color: "tips", "{row}" }
+            }
+            if !App3d::is_enabled() {
+                div { {tr("stereo.off")} }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disparity_follows_the_slider() {
+        assert_eq!(disparity(MAX_DEEP_3D, 0.0), 0.0);
+        assert_eq!(disparity(MAX_DEEP_3D, 1.0), 2.0 * MAX_DEEP_3D);
+        assert_eq!(disparity(-2.0, 0.5), -2.0);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for pattern in Stereo3dPattern::ALL {
+            assert_eq!(Stereo3dPattern::parse(pattern.name()), Some(pattern));
+        }
+    }
+}
@@ -0,0 +1,231 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::{color::rgba, constant::SCREEN_HEIGHT, i18n::tr_name};
+
+use super::{
+    cook_color::CookColor,
+    screen_fill::{screen_width, ScreenFill},
+};
+
+// diagnostic patterns, paged with the d-pad
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum TestPattern {
+    #[default]
+    Red,
+    Green,
+    Blue,
+    White,
+    Black,
+    Checkerboard,
+    CheckerboardLarge,
+    Grid,
+    GrayRamp,
+    Crosshair,
+    // smooth gradients, for banding and tint shifts
+    GrayGradient,
+    GrayGradientVertical,
+    RedGradient,
+    GreenGradient,
+    BlueGradient,
+    // 16 labelled bands from black to white
+    GraySteps,
+    // every other pixel column with one subpixel lit, for dead drive lines
+    SubpixelRed,
+    SubpixelGreen,
+    SubpixelBlue,
+    // white 1px lines, for column and row defects
+    // a top screen pixel covers two physical columns while 3d is off
+    Columns2,
+    Columns3,
+    Columns4,
+    Rows2,
+    Rows3,
+    Rows4,
+}
+
+impl TestPattern {
+    pub const ALL: [TestPattern; 25] = [
+        TestPattern::Red,
+        TestPattern::Green,
+        TestPattern::Blue,
+        TestPattern::White,
+        TestPattern::Black,
+        TestPattern::Checkerboard,
+        TestPattern::CheckerboardLarge,
+        TestPattern::Grid,
+        TestPattern::GrayRamp,
+        TestPattern::Crosshair,
+        TestPattern::GrayGradient,
+        TestPattern::GrayGradientVertical,
+        TestPattern::RedGradient,
+        TestPattern::GreenGradient,
+        TestPattern::BlueGradient,
+        TestPattern::GraySteps,
+        TestPattern::SubpixelRed,
+        TestPattern::SubpixelGreen,
+        TestPattern::SubpixelBlue,
+        TestPattern::Columns2,
+        TestPattern::Columns3,
+        TestPattern::Columns4,
+        TestPattern::Rows2,
+        TestPattern::Rows3,
+        TestPattern::Rows4,
+    ];
+
+    pub fn step(&self, forward: bool) -> Self {
+        let len = Self::ALL.len();
+        let idx = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        if forward {
+            Self::ALL[(idx + 1) % len]
+        } else {
+            Self::ALL[(idx + len - 1) % len]
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestPattern::Red => "red",
+            TestPattern::Green => "green",
+            TestPattern::Blue => "blue",
+            TestPattern::White => "white",
+            TestPattern::Black => "black",
+            TestPattern::Checkerboard => "checkerboard",
+            TestPattern::CheckerboardLarge => "checkerboard_large",
+            TestPattern::Grid => "grid",
+            TestPattern::GrayRamp => "gray_ramp",
+            TestPattern::Crosshair => "crosshair",
+            TestPattern::GrayGradient => "gray_gradient",
+            TestPattern::GrayGradientVertical => "gray_gradient_vertical",
+            TestPattern::RedGradient => "red_gradient",
+            TestPattern::GreenGradient => "green_gradient",
+            TestPattern::BlueGradient => "blue_gradient",
+            TestPattern::GraySteps => "gray_steps",
+            TestPattern::SubpixelRed => "subpixel_red",
+            TestPattern::SubpixelGreen => "subpixel_green",
+            TestPattern::SubpixelBlue => "subpixel_blue",
+            TestPattern::Columns2 => "columns_2",
+            TestPattern::Columns3 => "columns_3",
+            TestPattern::Columns4 => "columns_4",
+            TestPattern::Rows2 => "rows_2",
+            TestPattern::Rows3 => "rows_3",
+            TestPattern::Rows4 => "rows_4",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr_name("pattern", self.name())
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|pattern| pattern.name() == text)
+            .copied()
+    }
+
+    fn background(&self) -> CookColor {
+        match self {
+            TestPattern::Red => CookColor::Red,
+            TestPattern::Green => CookColor::Green,
+            TestPattern::Blue => CookColor::Blue,
+            TestPattern::White => CookColor::White,
+            _ => CookColor::Black,
+        }
+    }
+
+    // (pattern, cell or spacing) for the canvas, None for solid colors
+    fn canvas(&self) -> Option<(&'static str, i64)> {
+        match self {
+            TestPattern::Checkerboard => Some(("checkerboard", 8)),
+            TestPattern::CheckerboardLarge => Some(("checkerboard", 32)),
+            TestPattern::Grid => Some(("grid", 10)),
+            TestPattern::GrayRamp => Some(("gray_ramp", 0)),
+            TestPattern::Crosshair => Some(("crosshair", 0)),
+            _ => None,
+        }
+    }
+
+    // (to color, vertical, steps) for a gradient canvas starting from black
+    fn gradient(&self) -> Option<(u32, bool, i64)> {
+        let white = CookColor::White.rgba();
+        match self {
+            TestPattern::GrayGradient => Some((white, false, 0)),
+            TestPattern::GrayGradientVertical => Some((white, true, 0)),
+            TestPattern::RedGradient => Some((rgba(0xff, 0x00, 0x00, 0xff), false, 0)),
+            TestPattern::GreenGradient => Some((rgba(0x00, 0xff, 0x00, 0xff), false, 0)),
+            TestPattern::BlueGradient => Some((rgba(0x00, 0x00, 0xff, 0xff), false, 0)),
+            TestPattern::GraySteps => Some((white, true, 16)),
+            _ => None,
+        }
+    }
+
+    // (color, every, rows) for a lines canvas
+    fn lines(&self) -> Option<(u32, i64, bool)> {
+        let white = CookColor::White.rgba();
+        match self {
+            TestPattern::SubpixelRed => Some((rgba(0xff, 0x00, 0x00, 0xff), 2, false)),
+            TestPattern::SubpixelGreen => Some((rgba(0x00, 0xff, 0x00, 0xff), 2, false)),
+            TestPattern::SubpixelBlue => Some((rgba(0x00, 0x00, 0xff, 0xff), 2, false)),
+            TestPattern::Columns2 => Some((white, 2, false)),
+            TestPattern::Columns3 => Some((white, 3, false)),
+            TestPattern::Columns4 => Some((white, 4, false)),
+            TestPattern::Rows2 => Some((white, 2, true)),
+            TestPattern::Rows3 => Some((white, 3, true)),
+            TestPattern::Rows4 => Some((white, 4, true)),
+            _ => None,
+        }
+    }
+}
+
+#[component]
+pub fn TestPatternFill(screen: &'static str, pattern: TestPattern) -> Element {
+    rsx! {
+        ScreenFill { screen, color: pattern.background().rgba() }
+        {pattern.canvas().map(|(name, size)| rsx! {
+            canvas {
+                "screen": screen,
+                "pattern": name,
+                "cell": size,
+                "spacing": size,
+                "fg": CookColor::White.rgba() as i64,
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: screen_width(screen),
+                height: SCREEN_HEIGHT,
+            }
+        })}
+        {pattern.gradient().map(|(to, vertical, steps)| rsx! {
+            canvas {
+                "screen": screen,
+                "pattern": "gradient",
+                "from": CookColor::Black.rgba() as i64,
+                "to": to as i64,
+                "direction": if vertical { "vertical" } else { "horizontal" },
+                "steps": steps,
+                "labels": steps > 0,
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: screen_width(screen),
+                height: SCREEN_HEIGHT,
+            }
+        })}
+        {pattern.lines().map(|(color, every, rows)| rsx! {
+            canvas {
+                "screen": screen,
+                "pattern": "lines",
+                "fg": color as i64,
+                "every": every,
+                "rows": rows,
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: screen_width(screen),
+                height: SCREEN_HEIGHT,
+            }
+        })}
+    }
+}
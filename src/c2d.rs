@@ -8,7 +8,9 @@ use std::{
 
 use ctru::services::gfx::Gfx;
 
-use crate::{platform::setup_romfs, utils::str_to_c_null_term_bytes};
+use crate::{
+    platform::setup_romfs, render::backend::C2dImageTrait, utils::str_to_c_null_term_bytes,
+};
 
 extern "C" {
     fn c2d_raw_init();
@@ -38,11 +40,24 @@ extern "C" {
     fn c2d_raw_load_icon_from_buffer(buffer: *const c_void) -> *mut c_void;
     fn c2d_raw_load_dsi_icon_from_buffer(buffer: *const c_void) -> *mut c_void;
     fn c2d_raw_load_qrcode_from_buffer(buffer: *const c_void) -> *mut c_void;
+    fn c2d_raw_load_rgba_image(pixels: *const u8, width: u32, height: u32) -> *mut c_void;
+    fn c2d_raw_free_rgba_image(image: *mut c_void);
 
     // raw
     fn C2D_CreateScreenTarget(screen: c_uint, side: c_uint) -> *mut c_void;
     fn C3D_RenderTargetDelete(target: *mut c_void);
     fn c2d_drawrectsolid(x: f32, y: f32, z: f32, w: f32, h: f32, color: u32);
+    fn c2d_drawrectgradient(
+        x: f32,
+        y: f32,
+        z: f32,
+        w: f32,
+        h: f32,
+        color0: u32,
+        color1: u32,
+        color2: u32,
+        color3: u32,
+    );
     fn C2D_DrawLine(
         x0: f32,
         y0: f32,
@@ -93,16 +108,19 @@ pub struct C2dDrawParams {
     pub angle: f32,
 }
 
-pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
-    r as u32 | ((g as u32) << 8) | ((b as u32) << 16) | ((a as u32) << 24)
-}
-
 pub fn c2d_draw_rect(x: f32, y: f32, z: f32, w: f32, h: f32, color: u32) {
     unsafe {
         c2d_drawrectsolid(x, y, z, w, h, color);
     }
 }
 
+// corner colors are top left, top right, bottom left, bottom right
+pub fn c2d_draw_rect_gradient(x: f32, y: f32, z: f32, w: f32, h: f32, colors: [u32; 4]) {
+    unsafe {
+        c2d_drawrectgradient(x, y, z, w, h, colors[0], colors[1], colors[2], colors[3]);
+    }
+}
+
 pub fn c2d_draw_line(
     x0: f32,
     y0: f32,
@@ -176,29 +194,33 @@ impl Drop for C2dText {
     }
 }
 
-pub trait C2dImageTrait {
-    fn get_image(&self) -> &C2dImage;
+// how the image was made decides how it is freed
+#[derive(Clone, Copy, PartialEq)]
+enum C2dImageSource {
+    Buffer,
+    Sheet,
+    Rgba,
 }
 
 // 确保 C2dImage 的生命周期不超过 C2dSpriteSheet
 pub struct C2dImage {
-    from_sheet: bool,
+    source: C2dImageSource,
     pub ptr: *mut c_void,
 }
 
 impl C2dImageTrait for C2dImage {
-    fn get_image(&self) -> &C2dImage {
-        self
+    fn get_image(&self) -> Option<&C2dImage> {
+        Some(self)
     }
 }
 
 impl Drop for C2dImage {
     fn drop(&mut self) {
         unsafe {
-            if !self.from_sheet {
-                c2d_raw_free_image(self.ptr);
-            } else {
-                c2d_raw_free_image_from_sheet(self.ptr);
+            match self.source {
+                C2dImageSource::Buffer => c2d_raw_free_image(self.ptr),
+                C2dImageSource::Sheet => c2d_raw_free_image_from_sheet(self.ptr),
+                C2dImageSource::Rgba => c2d_raw_free_rgba_image(self.ptr),
             }
         }
     }
@@ -218,8 +240,8 @@ impl Deref for C2dImageFromSheet<'_> {
 }
 
 impl C2dImageTrait for C2dImageFromSheet<'_> {
-    fn get_image(&self) -> &C2dImage {
-        &self.image
+    fn get_image(&self) -> Option<&C2dImage> {
+        Some(&self.image)
     }
 }
 
@@ -251,7 +273,7 @@ impl C2dSpriteSheet {
         unsafe {
             C2dImageFromSheet {
                 image: C2dImage {
-                    from_sheet: true,
+                    source: C2dImageSource::Sheet,
                     ptr: c2d_raw_image_from_sheet(self.ptr, idx),
                 },
                 _phantom: PhantomData,
@@ -271,7 +293,7 @@ impl Drop for C2dSpriteSheet {
 pub fn c2d_load_icon_from_buffer(buffer: &[u16]) -> C2dImage {
     unsafe {
         C2dImage {
-            from_sheet: false,
+            source: C2dImageSource::Buffer,
             ptr: c2d_raw_load_icon_from_buffer(buffer.as_ptr() as *const c_void),
         }
     }
@@ -280,7 +302,7 @@ pub fn c2d_load_icon_from_buffer(buffer: &[u16]) -> C2dImage {
 pub fn c2d_load_dsi_icon_from_buffer(buffer: &[u16]) -> C2dImage {
     unsafe {
         C2dImage {
-            from_sheet: false,
+            source: C2dImageSource::Buffer,
             ptr: c2d_raw_load_dsi_icon_from_buffer(buffer.as_ptr() as *const c_void),
         }
     }
@@ -289,12 +311,24 @@ pub fn c2d_load_dsi_icon_from_buffer(buffer: &[u16]) -> C2dImage {
 pub fn c2d_load_qrcode_from_buffer(buffer: &[u8]) -> C2dImage {
     unsafe {
         C2dImage {
-            from_sheet: false,
+            source: C2dImageSource::Buffer,
             ptr: c2d_raw_load_qrcode_from_buffer(buffer.as_ptr() as *const c_void),
         }
     }
 }
 
+// rgba8 pixels row by row from the top, None if the gpu has no room for it
+pub fn c2d_load_rgba_image(pixels: &[u8], width: u32, height: u32) -> Option<C2dImage> {
+    if pixels.len() < width as usize * height as usize * 4 {
+        return None;
+    }
+    let ptr = unsafe { c2d_raw_load_rgba_image(pixels.as_ptr(), width, height) };
+    (!ptr.is_null()).then_some(C2dImage {
+        source: C2dImageSource::Rgba,
+        ptr,
+    })
+}
+
 pub struct C2D {
     pub gfx: Gfx,
     top_render_target_left: *mut c_void,
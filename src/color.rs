@@ -0,0 +1,49 @@
+//! rgba colors packed the way citro2d takes them, red in the low byte
+pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    r as u32 | ((g as u32) << 8) | ((b as u32) << 16) | ((a as u32) << 24)
+}
+
+// mix two colors channel by channel, t from 0.0 to 1.0
+pub fn lerp_rgba(start: u32, end: u32, t: f32) -> u32 {
+    let t = t.clamp(0.0, 1.0);
+    (0..4).fold(0, |color, shift| {
+        let from = (start >> (shift * 8)) & 0xff;
+        let to = (end >> (shift * 8)) & 0xff;
+        let value = (from as f32 + (to as f32 - from as f32) * t).round() as u32;
+        color | (value << (shift * 8))
+    })
+}
+
+// split `length` into `steps` whole pixel bands, (offset, size, color) for each
+pub fn gradient_bands(length: f32, start: u32, end: u32, steps: u32) -> Vec<(f32, f32, u32)> {
+    (0..steps)
+        .map(|step| {
+            let from = (length * step as f32 / steps as f32).round();
+            let to = (length * (step + 1) as f32 / steps as f32).round();
+            let t = if steps > 1 {
+                step as f32 / (steps - 1) as f32
+            } else {
+                0.0
+            };
+            (from, to - from, lerp_rgba(start, end, t))
+        })
+        .collect()
+}
+
+// from `start` at the left or top to `end` at the right or bottom
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Gradient {
+    pub start: u32,
+    pub end: u32,
+    pub vertical: bool,
+    // one vertex colored quad when 0, otherwise solid bands
+    pub steps: u32,
+}
+
+impl Gradient {
+    // (offset, size, color) of the bands for a `w` x `h` rect
+    pub fn bands(&self, w: f32, h: f32) -> Vec<(f32, f32, u32)> {
+        let length = if self.vertical { h } else { w };
+        gradient_bands(length, self.start, self.end, self.steps)
+    }
+}
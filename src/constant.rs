@@ -8,6 +8,10 @@ pub const SCREEN_HEIGHT: i64 = 240;
 pub const MAX_DEEP_3D: f32 = 5.0;
 
 pub const SAVE_LOG_PATH: &str = "/config/cook-screen/log/log.txt";
+pub const SAVE_SETTINGS_PATH: &str = "/config/cook-screen/settings.txt";
+pub const SAVE_HISTORY_PATH: &str = "/config/cook-screen/history.txt";
+pub const BURN_IN_IMAGES_DIR: &str = "/config/cook-screen/images";
+pub const RECIPES_DIR: &str = "/config/cook-screen/recipes";
 pub const INVALID_CHARS: [char; 10] = ['\\', '/', ':', '*', '?', '"', '\'', '<', '>', '|'];
 
 // version
@@ -0,0 +1,443 @@
+//! ui strings in every language, picked by the system language setting
+//!
+//! `{}` in a string is replaced by the arguments of `tr_args` in order.
+use std::{fmt::Display, sync::OnceLock};
+
+use crate::platform::get_system_language;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Language {
+    English,
+    Chinese,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Chinese];
+
+    // CFG_Language, simplified and traditional chinese both get the chinese table
+    pub fn from_system(code: u8) -> Self {
+        match code {
+            6 | 11 => Language::Chinese,
+            _ => Language::English,
+        }
+    }
+
+    fn table(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => EN,
+            Language::Chinese => ZH,
+        }
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+// read once, the system setting can not change while the app runs
+pub fn language() -> Language {
+    *LANGUAGE.get_or_init(|| {
+        get_system_language()
+            .map(Language::from_system)
+            .unwrap_or(Language::English)
+    })
+}
+
+fn lookup(language: Language, key: &str) -> Option<&'static str> {
+    language
+        .table()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, text)| *text)
+}
+
+// the english text when the language misses the key, the key when english does too
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(language(), key)
+        .or_else(|| lookup(Language::English, key))
+        .unwrap_or(key)
+}
+
+pub fn tr_args(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut parts = tr(key).split("{}");
+    let mut text = parts.next().unwrap_or_default().to_string();
+    for (idx, part) in parts.enumerate() {
+        if let Some(arg) = args.get(idx) {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
+
+// `kind.name` for the names saved in the settings, the name itself if it has no text
+pub fn tr_name(kind: &str, name: &str) -> String {
+    let key = format!("{}.{}", kind, name);
+    lookup(language(), &key)
+        .or_else(|| lookup(Language::English, &key))
+        .map_or_else(|| name.to_string(), |text| text.to_string())
+}
+
+const EN: &[(&str, &str)] = &[
+    // shared
+    ("common.on", "on"),
+    ("common.off", "off"),
+    ("common.save", "Save"),
+    ("common.close", "Close"),
+    ("common.unknown", "unknown"),
+    ("time.minutes", "{}m"),
+    ("time.hours", "{}h"),
+    ("screen.top", "top"),
+    ("screen.bottom", "bottom"),
+    (
+        "swkbd.invalid_chars",
+        r#"Can not contain these characters: \ /:*?"'<>|"#,
+    ),
+    // control panel
+    ("panel.screen", "Screen"),
+    ("panel.mode", "Mode"),
+    ("panel.color", "Color"),
+    ("panel.timer", "Timer"),
+    ("panel.countdown", "Countdown"),
+    ("panel.brightness", "Brightness"),
+    ("panel.brightness_system", "system"),
+    ("panel.picker", "Picker"),
+    ("panel.picker_value", "rgb/hsv"),
+    ("panel.recipe", "Recipe"),
+    ("panel.recipe_value", "edit"),
+    ("panel.history", "History"),
+    ("panel.history_value", "hours"),
+    // cook modes
+    ("mode.solid", "solid"),
+    ("mode.color_cycle", "color cycle"),
+    ("mode.exerciser", "exerciser"),
+    ("mode.test_pattern", "test pattern"),
+    ("mode.burn_in", "burn in"),
+    ("mode.stereo_3d", "3d test"),
+    // built in colors
+    ("color.white", "white"),
+    ("color.red", "red"),
+    ("color.green", "green"),
+    ("color.blue", "blue"),
+    ("color.black", "black"),
+    // test patterns
+    ("pattern.red", "red"),
+    ("pattern.green", "green"),
+    ("pattern.blue", "blue"),
+    ("pattern.white", "white"),
+    ("pattern.black", "black"),
+    ("pattern.checkerboard", "checkerboard"),
+    ("pattern.checkerboard_large", "large checkerboard"),
+    ("pattern.grid", "grid"),
+    ("pattern.gray_ramp", "gray ramp"),
+    ("pattern.crosshair", "crosshair"),
+    ("pattern.gray_gradient", "gray gradient"),
+    ("pattern.gray_gradient_vertical", "vertical gray gradient"),
+    ("pattern.red_gradient", "red gradient"),
+    ("pattern.green_gradient", "green gradient"),
+    ("pattern.blue_gradient", "blue gradient"),
+    ("pattern.gray_steps", "gray steps"),
+    ("pattern.subpixel_red", "red subpixels"),
+    ("pattern.subpixel_green", "green subpixels"),
+    ("pattern.subpixel_blue", "blue subpixels"),
+    ("pattern.columns_2", "every 2nd column"),
+    ("pattern.columns_3", "every 3rd column"),
+    ("pattern.columns_4", "every 4th column"),
+    ("pattern.rows_2", "every 2nd row"),
+    ("pattern.rows_3", "every 3rd row"),
+    ("pattern.rows_4", "every 4th row"),
+    // color picker, the channel letters label the sliders
+    ("picker.use", "Use"),
+    ("picker.preset", "preset"),
+    ("picker.cancel", "Cancel"),
+    ("picker.r", "R"),
+    ("picker.g", "G"),
+    ("picker.b", "B"),
+    ("picker.h", "H"),
+    ("picker.s", "S"),
+    ("picker.v", "V"),
+    // burn in
+    (
+        "burn_in.no_image",
+        "put png or bmp files in {}, then pick one with the d-pad",
+    ),
+    // recipes
+    ("recipe.new", "new recipe"),
+    ("recipe.unnamed", "recipe"),
+    ("recipe.editor_tips", "L/R step, Y mode, X length, B close"),
+    ("recipe.add", "Add"),
+    ("recipe.remove", "Remove"),
+    ("recipe.step", "step"),
+    ("recipe.at_end", "At end"),
+    ("recipe.exit", "exit"),
+    ("recipe.keep", "keep"),
+    ("recipe.load", "Load"),
+    ("recipe.next", "next"),
+    ("recipe.save_as", "as"),
+    ("recipe.run", "Run"),
+    ("recipe.done", "done"),
+    ("recipe.paused", "{} paused"),
+    ("recipe.status_tips", "A pause/resume, B stop"),
+    // history
+    ("history.title", "cooked per console"),
+    ("history.empty", "no sessions yet"),
+    ("history.this_console", " (this)"),
+    ("history.total", "{}{} {}h, {} sessions"),
+    ("end.timer", "timer"),
+    ("end.recipe", "recipe"),
+    ("end.start", "start"),
+    ("end.home_menu", "home menu"),
+    ("end.power", "power"),
+    // 3d test
+    ("stereo_pattern.depth_grid", "depth grid"),
+    ("stereo_pattern.crosshair", "crosshair"),
+    ("stereo_pattern.crosstalk", "crosstalk"),
+    ("stereo.title", "{}, 3d slider {}"),
+    ("stereo.depth", "depth {}: {} px"),
+    ("stereo.off", "3d is off in the settings"),
+    ("stereo.left_only", "left eye only"),
+    ("stereo.right_only", "right eye only"),
+    // help
+    ("help.title", "controls"),
+    ("help.exit", "exit"),
+    ("help.help", "show or hide this help"),
+    ("help.timer", "session length"),
+    ("help.countdown", "show or hide the countdown"),
+    ("help.next_mode", "next mode"),
+    ("help.darker", "darker"),
+    ("help.brighter", "brighter"),
+    ("help.select_top", "configure the top screen"),
+    ("help.select_bottom", "configure the bottom screen"),
+    ("help.mode_keys", "change the mode settings"),
+    ("help.lock", "lock every button and the touch screen"),
+    // input lock
+    ("lock.notice", "locked, hold {} for {}s to unlock"),
+];
+
+const ZH: &[(&str, &str)] = &[
+    // shared
+    ("common.on", "开"),
+    ("common.off", "关"),
+    ("common.save", "保存"),
+    ("common.close", "关闭"),
+    ("common.unknown", "未知"),
+    ("time.minutes", "{}分钟"),
+    ("time.hours", "{}小时"),
+    ("screen.top", "上屏"),
+    ("screen.bottom", "下屏"),
+    ("swkbd.invalid_chars", r#"不能包含此类字符: \ /:*?"'<>|"#),
+    // control panel
+    ("panel.screen", "屏幕"),
+    ("panel.mode", "模式"),
+    ("panel.color", "颜色"),
+    ("panel.timer", "定时"),
+    ("panel.countdown", "倒计时"),
+    ("panel.brightness", "亮度"),
+    ("panel.brightness_system", "系统"),
+    ("panel.picker", "取色"),
+    ("panel.picker_value", "rgb/hsv"),
+    ("panel.recipe", "方案"),
+    ("panel.recipe_value", "编辑"),
+    ("panel.history", "记录"),
+    ("panel.history_value", "时长"),
+    // cook modes
+    ("mode.solid", "纯色"),
+    ("mode.color_cycle", "颜色循环"),
+    ("mode.exerciser", "像素激活"),
+    ("mode.test_pattern", "测试图案"),
+    ("mode.burn_in", "残影修复"),
+    ("mode.stereo_3d", "3D 测试"),
+    // built in colors
+    ("color.white", "白"),
+    ("color.red", "红"),
+    ("color.green", "绿"),
+    ("color.blue", "蓝"),
+    ("color.black", "黑"),
+    // test patterns
+    ("pattern.red", "红"),
+    ("pattern.green", "绿"),
+    ("pattern.blue", "蓝"),
+    ("pattern.white", "白"),
+    ("pattern.black", "黑"),
+    ("pattern.checkerboard", "棋盘格"),
+    ("pattern.checkerboard_large", "大棋盘格"),
+    ("pattern.grid", "网格"),
+    ("pattern.gray_ramp", "灰阶"),
+    ("pattern.crosshair", "十字线"),
+    ("pattern.gray_gradient", "灰色渐变"),
+    ("pattern.gray_gradient_vertical", "纵向灰色渐变"),
+    ("pattern.red_gradient", "红色渐变"),
+    ("pattern.green_gradient", "绿色渐变"),
+    ("pattern.blue_gradient", "蓝色渐变"),
+    ("pattern.gray_steps", "灰阶色块"),
+    ("pattern.subpixel_red", "红色子像素"),
+    ("pattern.subpixel_green", "绿色子像素"),
+    ("pattern.subpixel_blue", "蓝色子像素"),
+    ("pattern.columns_2", "每 2 列"),
+    ("pattern.columns_3", "每 3 列"),
+    ("pattern.columns_4", "每 4 列"),
+    ("pattern.rows_2", "每 2 行"),
+    ("pattern.rows_3", "每 3 行"),
+    ("pattern.rows_4", "每 4 行"),
+    // color picker, the channel letters label the sliders
+    ("picker.use", "使用"),
+    ("picker.preset", "预设"),
+    ("picker.cancel", "取消"),
+    ("picker.r", "红"),
+    ("picker.g", "绿"),
+    ("picker.b", "蓝"),
+    ("picker.h", "色"),
+    ("picker.s", "饱"),
+    ("picker.v", "明"),
+    // burn in
+    (
+        "burn_in.no_image",
+        "请把 png 或 bmp 文件放到 {}，然后用十字键选择",
+    ),
+    // recipes
+    ("recipe.new", "新方案"),
+    ("recipe.unnamed", "方案"),
+    ("recipe.editor_tips", "L/R 选步骤, Y 模式, X 时长, B 关闭"),
+    ("recipe.add", "添加"),
+    ("recipe.remove", "删除"),
+    ("recipe.step", "步骤"),
+    ("recipe.at_end", "结束时"),
+    ("recipe.exit", "退出"),
+    ("recipe.keep", "保持"),
+    ("recipe.load", "载入"),
+    ("recipe.next", "下一个"),
+    ("recipe.save_as", "另存"),
+    ("recipe.run", "运行"),
+    ("recipe.done", "完成"),
+    ("recipe.paused", "{} 已暂停"),
+    ("recipe.status_tips", "A 暂停/继续, B 停止"),
+    // history
+    ("history.title", "每台主机的累计时长"),
+    ("history.empty", "还没有记录"),
+    ("history.this_console", " (本机)"),
+    ("history.total", "{}{} {}小时, {} 次"),
+    ("end.timer", "定时结束"),
+    ("end.recipe", "方案结束"),
+    ("end.start", "Start 键"),
+    ("end.home_menu", "主菜单"),
+    ("end.power", "电源键"),
+    // 3d test
+    ("stereo_pattern.depth_grid", "深度网格"),
+    ("stereo_pattern.crosshair", "十字线"),
+    ("stereo_pattern.crosstalk", "串扰"),
+    ("stereo.title", "{}, 3D 滑块 {}"),
+    ("stereo.depth", "深度 {}: {} 像素"),
+    ("stereo.off", "设置里关闭了 3D"),
+    ("stereo.left_only", "仅左眼"),
+    ("stereo.right_only", "仅右眼"),
+    // help
+    ("help.title", "按键说明"),
+    ("help.exit", "退出"),
+    ("help.help", "显示或隐藏说明"),
+    ("help.timer", "运行时长"),
+    ("help.countdown", "显示或隐藏倒计时"),
+    ("help.next_mode", "下一个模式"),
+    ("help.darker", "调暗"),
+    ("help.brighter", "调亮"),
+    ("help.select_top", "设置上屏"),
+    ("help.select_bottom", "设置下屏"),
+    ("help.mode_keys", "调整当前模式"),
+    ("help.lock", "锁定所有按键和触摸屏"),
+    // input lock
+    ("lock.notice", "已锁定, 按住 {} {} 秒解锁"),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::app::{
+        cook_color::COOK_COLORS, cook_mode::CookMode, history::EndReason, key_binding::Action,
+        stereo_3d::Stereo3dPattern, test_pattern::TestPattern,
+    };
+
+    fn keys(language: Language) -> BTreeSet<&'static str> {
+        language.table().iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_language() {
+        let english = keys(Language::English);
+        for language in Language::ALL {
+            assert_eq!(keys(language), english, "{:?}", language);
+        }
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        for language in Language::ALL {
+            assert_eq!(
+                keys(language).len(),
+                language.table().len(),
+                "{:?}",
+                language
+            );
+        }
+    }
+
+    #[test]
+    fn placeholders_match() {
+        for (key, text) in EN {
+            for language in Language::ALL {
+                let translated = lookup(language, key).unwrap();
+                assert_eq!(
+                    translated.matches("{}").count(),
+                    text.matches("{}").count(),
+                    "{} in {:?}",
+                    key,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_name_has_a_text() {
+        let mut names = vec![];
+        names.extend(
+            CookMode::ALL
+                .iter()
+                .map(|mode| format!("mode.{}", mode.name())),
+        );
+        names.extend(
+            COOK_COLORS
+                .iter()
+                .map(|color| format!("color.{}", color.name())),
+        );
+        names.extend(
+            TestPattern::ALL
+                .iter()
+                .map(|pattern| format!("pattern.{}", pattern.name())),
+        );
+        names.extend(
+            Stereo3dPattern::ALL
+                .iter()
+                .map(|pattern| format!("stereo_pattern.{}", pattern.name())),
+        );
+        names.extend(
+            EndReason::ALL
+                .iter()
+                .map(|end| format!("end.{}", end.name())),
+        );
+        names.extend(
+            Action::ALL
+                .iter()
+                .map(|action| format!("help.{}", action.name())),
+        );
+        let english = keys(Language::English);
+        for name in names {
+            assert!(english.contains(name.as_str()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn system_language() {
+        assert_eq!(Language::from_system(6), Language::Chinese);
+        assert_eq!(Language::from_system(11), Language::Chinese);
+        assert_eq!(Language::from_system(1), Language::English);
+    }
+}
@@ -0,0 +1,274 @@
+//! png and bmp files from the sd card, decoded to rgba8 rows from the top
+use std::{fs, io, path::Path};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    // 4 bytes per pixel, row by row from the top
+    pub pixels: Vec<u8>,
+}
+
+// larger than both screens, a broken header can not ask for gigabytes
+const MAX_SIZE: u32 = 1024;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn check_size(width: u32, height: u32) -> io::Result<()> {
+    if width > MAX_SIZE || height > MAX_SIZE {
+        return Err(invalid("image is larger than 1024x1024"));
+    }
+    Ok(())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> io::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| invalid("bmp header is too short"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> io::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| invalid("bmp header is too short"))
+}
+
+impl RgbaImage {
+    // every decoder ends here, so resize can trust the pixel count
+    fn new(width: u32, height: u32, pixels: Vec<u8>) -> io::Result<Self> {
+        check_size(width, height)?;
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(invalid("image pixels do not match its size"));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => Self::decode_png(&data),
+            Some("bmp") => Self::decode_bmp(&data),
+            _ => Err(invalid("only png and bmp files are supported")),
+        }
+    }
+
+    pub fn decode_png(data: &[u8]) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|err| invalid(&err.to_string()))?;
+        check_size(reader.info().width, reader.info().height)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buf)
+            .map_err(|err| invalid(&err.to_string()))?;
+        let buf = &buf[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf.to_vec(),
+            png::ColorType::Rgb => buf
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 0xff])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&l| [l, l, l, 0xff]).collect(),
+            png::ColorType::Indexed => return Err(invalid("png palette was not expanded")),
+        };
+        Self::new(info.width, info.height, pixels)
+    }
+
+    // uncompressed 24 and 32 bit files, what most tools save
+    pub fn decode_bmp(data: &[u8]) -> io::Result<Self> {
+        if data.get(0..2) != Some(b"BM") {
+            return Err(invalid("not a bmp file"));
+        }
+        let offset = read_u32(data, 10)? as usize;
+        let width = read_u32(data, 18)? as i32;
+        let height = read_u32(data, 22)? as i32;
+        let bits = read_u16(data, 28)?;
+        let compression = read_u32(data, 30)?;
+        // 3 is BI_BITFIELDS, 32 bit files use it with the usual bgra masks
+        if !(compression == 0 || compression == 3 && bits == 32) {
+            return Err(invalid("compressed bmp files are not supported"));
+        }
+        if bits != 24 && bits != 32 {
+            return Err(invalid("only 24 and 32 bit bmp files are supported"));
+        }
+        if width <= 0 || height == 0 {
+            return Err(invalid("bmp has no pixels"));
+        }
+        let (width, bottom_up) = (width as u32, height > 0);
+        let height = height.unsigned_abs();
+        check_size(width, height)?;
+        let bytes_per_pixel = bits as usize / 8;
+        // rows are padded to 4 bytes
+        let stride = (width as usize * bytes_per_pixel + 3) & !3;
+        let end = stride
+            .checked_mul(height as usize)
+            .and_then(|size| size.checked_add(offset));
+        if end.is_none_or(|end| data.len() < end) {
+            return Err(invalid("bmp pixel data is too short"));
+        }
+
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height as usize {
+            let row = if bottom_up {
+                height as usize - 1 - y
+            } else {
+                y
+            };
+            let start = offset + row * stride;
+            for p in
+                data[start..start + width as usize * bytes_per_pixel].chunks_exact(bytes_per_pixel)
+            {
+                pixels.extend([p[2], p[1], p[0], 0xff]);
+            }
+        }
+        Self::new(width, height, pixels)
+    }
+
+    // the complementary color of every pixel, alpha is kept
+    pub fn invert(&mut self) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel[0] = 0xff - pixel[0];
+            pixel[1] = 0xff - pixel[1];
+            pixel[2] = 0xff - pixel[2];
+        }
+    }
+
+    // nearest neighbour, so a capture of the same screen stays pixel exact
+    pub fn resize(&self, width: u32, height: u32) -> Self {
+        if width == self.width && height == self.height {
+            return self.clone();
+        }
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            let src_y = (y as u64 * self.height as u64 / height.max(1) as u64) as usize;
+            for x in 0..width {
+                let src_x = (x as u64 * self.width as u64 / width.max(1) as u64) as usize;
+                let idx = (src_y * self.width as usize + src_x) * 4;
+                // transparent if the pixels were changed by hand
+                let pixel = self.pixels.get(idx..idx + 4).unwrap_or(&[0; 4]);
+                pixels.extend_from_slice(pixel);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 2x2 24 bit bmp, bottom row first
+    fn bmp_2x2(bottom_up: bool) -> Vec<u8> {
+        let mut data = vec![0; 54];
+        data[0..2].copy_from_slice(b"BM");
+        data[10..14].copy_from_slice(&54_u32.to_le_bytes());
+        data[14..18].copy_from_slice(&40_u32.to_le_bytes());
+        data[18..22].copy_from_slice(&2_i32.to_le_bytes());
+        let height: i32 = if bottom_up { 2 } else { -2 };
+        data[22..26].copy_from_slice(&height.to_le_bytes());
+        data[26..28].copy_from_slice(&1_u16.to_le_bytes());
+        data[28..30].copy_from_slice(&24_u16.to_le_bytes());
+        // bgr pixels, each row padded from 6 to 8 bytes
+        let top = [0x00, 0x00, 0xff, 0x00, 0xff, 0x00, 0, 0];
+        let bottom = [0xff, 0x00, 0x00, 0xff, 0xff, 0xff, 0, 0];
+        if bottom_up {
+            data.extend(bottom);
+            data.extend(top);
+        } else {
+            data.extend(top);
+            data.extend(bottom);
+        }
+        data
+    }
+
+    const PIXELS_2X2: [u8; 16] = [
+        0xff, 0x00, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, // red, green
+        0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // blue, white
+    ];
+
+    #[test]
+    fn bmp_rows_are_read_from_the_top() {
+        for bottom_up in [true, false] {
+            let image = RgbaImage::decode_bmp(&bmp_2x2(bottom_up)).unwrap();
+            assert_eq!((image.width, image.height), (2, 2));
+            assert_eq!(image.pixels, PIXELS_2X2);
+        }
+    }
+
+    #[test]
+    fn broken_bmp_is_an_error() {
+        let data = bmp_2x2(true);
+        assert!(RgbaImage::decode_bmp(&data[..60]).is_err());
+        assert!(RgbaImage::decode_bmp(b"PNG").is_err());
+    }
+
+    #[test]
+    fn bad_bmp_header_is_an_error() {
+        let set = |offset: usize, value: u32| {
+            let mut data = bmp_2x2(true);
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            RgbaImage::decode_bmp(&data)
+        };
+        // pixel data offset past the end of a 32 bit address space
+        assert!(set(10, u32::MAX).is_err());
+        // huge and negative sizes
+        assert!(set(18, i32::MAX as u32).is_err());
+        assert!(set(22, i32::MIN as u32).is_err());
+        assert!(set(18, 1025).is_err());
+        // the size fits, the data does not
+        assert!(set(22, 1024).is_err());
+    }
+
+    #[test]
+    fn png_is_decoded() {
+        let mut data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut data, 2, 2);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&PIXELS_2X2).unwrap();
+        }
+        let image = RgbaImage::decode_png(&data).unwrap();
+        assert_eq!(image.pixels, PIXELS_2X2);
+    }
+
+    #[test]
+    fn invert_keeps_alpha() {
+        let mut image = RgbaImage {
+            width: 1,
+            height: 1,
+            pixels: vec![0x00, 0x80, 0xff, 0x40],
+        };
+        image.invert();
+        assert_eq!(image.pixels, [0xff, 0x7f, 0x00, 0x40]);
+    }
+
+    #[test]
+    fn resize_picks_nearest_pixels() {
+        let image = RgbaImage::decode_bmp(&bmp_2x2(true)).unwrap();
+        let large = image.resize(4, 4);
+        assert_eq!(&large.pixels[0..8], &PIXELS_2X2[0..4].repeat(2)[..]);
+        assert_eq!(large.resize(2, 2), image);
+    }
+}
@@ -1,8 +1,13 @@
 pub mod app;
+#[cfg(target_os = "horizon")]
 pub mod c2d;
+pub mod color;
 pub mod constant;
+pub mod i18n;
+pub mod image_file;
 pub mod log;
 pub mod platform;
 pub mod render;
 pub mod resource;
+pub mod settings;
 pub mod utils;
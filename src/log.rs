@@ -11,7 +11,7 @@
 //!# Usage
 //!
 //!## Add dependency
-//!```
+//!```text
 //!cargo add log2
 //!```
 //!
@@ -19,7 +19,7 @@
 //!
 //!Simple to start.
 //!
-//!```rust
+//!```ignore
 //!use log2::*;
 //!
 //!fn main() {
@@ -39,7 +39,7 @@
 //!
 //!Show module path, and set log level.
 //!
-//!```rust
+//!```ignore
 //!use log2::*;
 //!
 //!fn main() {
@@ -62,7 +62,7 @@
 //!`log2` with default file size 100MB, max file count 10, you can change as you like. Note the `_log2` will
 //!stop the log2 instance when it is out of the scope
 //!
-//!```rust
+//!```ignore
 //!use log2::*;
 //!
 //!fn main() {
@@ -91,7 +91,7 @@
 //!
 //!Output files
 //!
-//!```
+//!```text
 //!log.txt
 //!log.1.txt
 //!log.2.txt
@@ -145,6 +145,12 @@ pub struct Log2 {
     lock: std::sync::Mutex<()>,
 }
 
+impl Default for Log2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Log2 {
     pub fn new() -> Self {
         Self {
@@ -173,7 +179,7 @@ impl Log2 {
     /// setup the maximum size for each file
     pub fn size(mut self, file_size: u64) -> Log2 {
         if self.count <= 1 {
-            self.file_size = std::u64::MAX;
+            self.file_size = u64::MAX;
         } else {
             self.file_size = file_size;
         }
@@ -184,7 +190,7 @@ impl Log2 {
     pub fn rotate(mut self, count: usize) -> Log2 {
         self.count = count;
         if self.count <= 1 {
-            self.file_size = std::u64::MAX;
+            self.file_size = u64::MAX;
         }
         self
     }
@@ -258,7 +264,7 @@ impl Log2 {
     pub fn do_rotate(path: &str, count: usize) -> Result<(), std::io::Error> {
         let dot = path.rfind(".").unwrap_or(0);
         let mut suffix = "";
-        let mut prefix = &path[..];
+        let mut prefix = path;
         if dot > 0 {
             suffix = &path[dot..];
             prefix = &path[0..dot];
@@ -307,7 +313,7 @@ impl log::Log for Log2 {
             }
         }
 
-        if self.tee || self.path.len() > 0 {
+        if self.tee || !self.path.is_empty() {
             let line = format!(
                 "[{}] [{}] {module}{}\n",
                 &get_current_format_time(),
@@ -321,7 +327,7 @@ impl log::Log for Log2 {
             }
 
             // file
-            if self.path.len() > 0 {
+            if !self.path.is_empty() {
                 self.do_write_line_to_file(line);
             }
         }
@@ -337,7 +343,7 @@ pub fn open(path: &str) -> Log2 {
     // create directory
     let dir = std::path::Path::new(path);
     if let Some(dir) = dir.parent() {
-        let _ = std::fs::create_dir_all(&dir);
+        let _ = std::fs::create_dir_all(dir);
     }
 
     let mut logger = Log2::new();
@@ -1,14 +1,31 @@
-use cook_screen::{app, constant::VERSION_STR, render, resource::Resource};
+#[cfg(target_os = "horizon")]
+use cook_screen::{
+    app::{self, history::SessionLog},
+    constant::VERSION_STR,
+    render,
+    resource::Resource,
+};
+#[cfg(target_os = "horizon")]
 use log::{error, info};
 
+#[cfg(target_os = "horizon")]
 fn main() {
     if let Err(err) = match Resource::new(false) {
         Ok(resource) => {
             info!("Cook Screen {}", VERSION_STR);
-            render::launch(app::Main, resource)
+            let result = render::launch(app::Main, resource);
+            // also when the app was closed from the home menu or the power button
+            SessionLog::save();
+            result
         }
         Err(err) => Err(err),
     } {
         error!("Failed to initialize app: {:?}", err);
     }
 }
+
+// the host only builds the library for the tests
+#[cfg(not(target_os = "horizon"))]
+fn main() {
+    eprintln!("cook screen runs on the 3ds, build it with `cargo 3ds build`");
+}
@@ -0,0 +1,269 @@
+use std::{cell::Cell, error::Error};
+
+pub use ctru::services::hid::KeyPad;
+use ctru::{
+    applets::swkbd::{Button, ButtonConfig, CallbackResult, Kind, SoftwareKeyboard},
+    os,
+    services::{self, romfs::RomFS, soc::Soc},
+};
+
+use crate::{constant::INVALID_CHARS, i18n::tr, resource::Resource};
+
+use super::{LcdScreen, MAX_BACKLIGHT_LEVEL, MIN_BACKLIGHT_LEVEL};
+
+extern "C" {
+    /**
+     * @brief Returns the Wifi signal strength.
+     *
+     * Valid values are 0-3:
+     * - 0 means the singal strength is terrible or the 3DS is disconnected from
+     *   all networks.
+     * - 1 means the signal strength is bad.
+     * - 2 means the signal strength is decent.
+     * - 3 means the signal strength is good.
+     *
+     * Values outside the range of 0-3 should never be returned.
+     *
+     * These values correspond with the number of wifi bars displayed by Home Menu.
+     *
+     * @return the Wifi signal strength
+     */
+    // fn pl_get_wifi_strength() -> c_uchar;
+    fn pl_is_n3ds() -> bool;
+    fn pl_lcd_exit();
+    fn pl_get_brightness_raw(screen: u32, brightness: *mut u32) -> bool;
+    fn pl_set_brightness_raw(screen: u32, brightness: u32) -> bool;
+    fn pl_set_brightness(screen: u32, level: u32) -> bool;
+    fn pl_power_on_backlights() -> bool;
+    fn pl_is_sleep_allowed() -> bool;
+    fn pl_set_sleep_allowed(allowed: bool);
+    fn pl_get_shell_open(open: *mut bool) -> bool;
+    fn pl_ptmu_exit();
+    fn pl_watch_home_menu();
+    fn pl_is_in_home_menu() -> bool;
+    fn pl_get_console_id(id: *mut u64) -> bool;
+    fn pl_get_system_language(language: *mut u8) -> bool;
+    // os function
+    fn osSetSpeedupEnable(enable: bool);
+}
+
+pub fn setup_log_redirect(soc: &mut Soc) -> Option<()> {
+    // if !cfg!(debug_assertions) {
+    //     return None;
+    // }
+    // Set the output to be redirected to the `3dslink` server.
+    soc.redirect_to_3dslink(true, true).ok()
+}
+
+pub fn setup_romfs() -> Result<RomFS, Box<dyn Error>> {
+    services::romfs::RomFS::new().map_err(|e| e.into())
+}
+
+pub fn enable_hight_performance_for_new_3ds() {
+    unsafe { osSetSpeedupEnable(true) }
+}
+
+// pub fn get_wifi_strength() -> u8 {
+//     unsafe { pl_get_wifi_strength() }
+// }
+
+pub fn is_new_3ds() -> bool {
+    unsafe { pl_is_n3ds() }
+}
+
+// 0.0 when the slider is down, up to 1.0
+pub fn current_3d_slider_state() -> f32 {
+    os::current_3d_slider_state()
+}
+
+impl LcdScreen {
+    fn index(self) -> usize {
+        match self {
+            LcdScreen::Top => 0,
+            LcdScreen::Bottom => 1,
+        }
+    }
+}
+
+pub fn get_backlight_raw(screen: LcdScreen) -> Option<u32> {
+    let mut brightness = 0;
+    unsafe { pl_get_brightness_raw(screen as u32, &mut brightness) }.then_some(brightness)
+}
+
+pub fn set_backlight_raw(screen: LcdScreen, brightness: u32) -> bool {
+    unsafe { pl_set_brightness_raw(screen as u32, brightness) }
+}
+
+pub fn set_backlight_level(screen: LcdScreen, level: u8) -> bool {
+    let level = level.clamp(MIN_BACKLIGHT_LEVEL, MAX_BACKLIGHT_LEVEL);
+    unsafe { pl_set_brightness(screen as u32, level as u32) }
+}
+
+// remembers the brightness the app started with and puts it back when dropped
+pub struct Backlight {
+    original: [Option<u32>; 2],
+    // the brightness the app wants, put back when the system dims the screen
+    pinned: Cell<[Option<u32>; 2]>,
+}
+
+impl Backlight {
+    pub fn new() -> Self {
+        let original = [
+            get_backlight_raw(LcdScreen::Top),
+            get_backlight_raw(LcdScreen::Bottom),
+        ];
+        Self {
+            original,
+            pinned: Cell::new(original),
+        }
+    }
+
+    // 0 goes back to the brightness from before the app started
+    pub fn set_level(&self, screen: LcdScreen, level: u8) -> bool {
+        let ok = if level == 0 {
+            self.restore(screen)
+        } else {
+            set_backlight_level(screen, level)
+        };
+        let mut pinned = self.pinned.get();
+        pinned[screen.index()] = get_backlight_raw(screen);
+        self.pinned.set(pinned);
+        ok
+    }
+
+    pub fn restore(&self, screen: LcdScreen) -> bool {
+        self.original[screen.index()]
+            .map(|brightness| set_backlight_raw(screen, brightness))
+            .unwrap_or(false)
+    }
+
+    // undo auto-dimming, true if a backlight had changed
+    pub fn keep_pinned(&self) -> bool {
+        let mut changed = false;
+        for screen in [LcdScreen::Top, LcdScreen::Bottom] {
+            if let Some(brightness) = self.pinned.get()[screen.index()] {
+                if get_backlight_raw(screen).is_some_and(|current| current != brightness) {
+                    changed |= set_backlight_raw(screen, brightness);
+                }
+            }
+        }
+        changed
+    }
+
+    // the backlights are switched off while the lid is closed
+    pub fn power_on(&self) -> bool {
+        let ok = unsafe { pl_power_on_backlights() };
+        self.keep_pinned();
+        ok
+    }
+}
+
+impl Default for Backlight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Backlight {
+    fn drop(&mut self) {
+        self.restore(LcdScreen::Top);
+        self.restore(LcdScreen::Bottom);
+        unsafe { pl_lcd_exit() }
+    }
+}
+
+// keeps the console from sleeping while the app runs, allowed again when dropped
+pub struct SleepGuard {
+    was_allowed: bool,
+}
+
+impl SleepGuard {
+    pub fn new() -> Self {
+        let was_allowed = unsafe { pl_is_sleep_allowed() };
+        unsafe { pl_set_sleep_allowed(false) };
+        Self { was_allowed }
+    }
+}
+
+impl Default for SleepGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SleepGuard {
+    fn drop(&mut self) {
+        unsafe {
+            pl_set_sleep_allowed(self.was_allowed);
+            pl_ptmu_exit();
+        }
+    }
+}
+
+// None if the lid state can not be read
+pub fn is_shell_open() -> Option<bool> {
+    let mut open = true;
+    unsafe { pl_get_shell_open(&mut open) }.then_some(open)
+}
+
+// called once apt is up
+pub fn watch_home_menu() {
+    unsafe { pl_watch_home_menu() }
+}
+
+// true while the app is suspended in the home menu
+pub fn is_in_home_menu() -> bool {
+    unsafe { pl_is_in_home_menu() }
+}
+
+// a hash unique to the console, as hex
+pub fn get_console_id() -> Option<String> {
+    let mut id = 0;
+    unsafe { pl_get_console_id(&mut id) }.then(|| format!("{:016x}", id))
+}
+
+// CFG_Language, None if the settings can not be read
+pub fn get_system_language() -> Option<u8> {
+    let mut language = 0;
+    unsafe { pl_get_system_language(&mut language) }.then_some(language)
+}
+
+pub fn pl_show_swkbd(resource: &Resource, initial_text: &str) -> Option<String> {
+    // Prepares a software keyboard with two buttons: one to cancel input and one
+    // to accept it. You can also use `SoftwareKeyboard::new()` to launch the keyboard
+    // with different configurations.
+    let mut keyboard = SoftwareKeyboard::new(Kind::Normal, ButtonConfig::LeftRight);
+
+    // Custom filter callback to handle the given input.
+    // Using this callback it's possible to integrate the applet
+    // with custom error messages when the input is incorrect.
+    keyboard.set_filter_callback(Some(Box::new(move |str| {
+        for c in INVALID_CHARS.iter() {
+            if str.contains(*c) {
+                return (
+                    CallbackResult::Retry,
+                    Some(tr("swkbd.invalid_chars").into()),
+                );
+            }
+        }
+
+        (CallbackResult::Ok, None)
+    })));
+
+    keyboard.set_initial_text(Some(initial_text));
+
+    // Launch the software keyboard. You can perform different actions depending on which
+    // software button the user pressed.
+    match keyboard.launch(&resource.apt, &resource.c2d.gfx) {
+        Ok((text, Button::Right)) => {
+            if text.is_empty() {
+                None
+            } else {
+                Some(text)
+            }
+        }
+        Ok((_, Button::Left)) => None,
+        Ok((_, Button::Middle)) => None,
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,69 @@
+//! stand-ins for the console services, so the ui runs under the software backend
+use bitflags::bitflags;
+
+use crate::resource::Resource;
+
+use super::LcdScreen;
+
+bitflags! {
+    // the same bits as the hid keypad of libctru
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+    pub struct KeyPad: u32 {
+        const A = 1 << 0;
+        const B = 1 << 1;
+        const SELECT = 1 << 2;
+        const START = 1 << 3;
+        const DPAD_RIGHT = 1 << 4;
+        const DPAD_LEFT = 1 << 5;
+        const DPAD_UP = 1 << 6;
+        const DPAD_DOWN = 1 << 7;
+        const R = 1 << 8;
+        const L = 1 << 9;
+        const X = 1 << 10;
+        const Y = 1 << 11;
+        const ZL = 1 << 14;
+        const ZR = 1 << 15;
+        const TOUCH = 1 << 20;
+    }
+}
+
+// frames are timed like on the new 3ds
+pub fn is_new_3ds() -> bool {
+    true
+}
+
+pub fn current_3d_slider_state() -> f32 {
+    0.0
+}
+
+// the host has no backlights, every level is taken
+#[derive(Default)]
+pub struct Backlight;
+
+impl Backlight {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn set_level(&self, _screen: LcdScreen, _level: u8) -> bool {
+        true
+    }
+}
+
+pub fn is_in_home_menu() -> bool {
+    false
+}
+
+pub fn get_console_id() -> Option<String> {
+    None
+}
+
+// the default language is used
+pub fn get_system_language() -> Option<u8> {
+    None
+}
+
+// there is no keyboard applet, like cancelling it
+pub fn pl_show_swkbd(_resource: &Resource, _initial_text: &str) -> Option<String> {
+    None
+}
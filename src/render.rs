@@ -1,43 +1,146 @@
 use std::{
+    collections::HashMap,
     rc::Rc,
     sync::{Arc, Mutex},
 };
 
-use ctru::os::current_3d_slider_state;
 use dioxus::prelude::*;
 use dioxus_native_core::{
     node::{OwnedAttributeDiscription, OwnedAttributeValue},
     prelude::*,
     tree::TreeRef,
+    NodeId,
 };
 use taffy::{prelude::*, Point};
 
-use crate::{
-    app::AppExit,
-    c2d::{
-        c2d_draw_image, c2d_draw_rect, c2d_draw_text, c2d_draw_text_wrap, C2dImageTrait, C2dText,
-    },
-    constant::{SCREEN_HEIGHT, SCREEN_TOP_WIDTH},
-    resource::Resource,
-    utils::{sleep_micros, sleep_micros_for_ever},
-};
+use crate::constant::{SCREEN_HEIGHT, SCREEN_TOP_WIDTH};
 
+#[cfg(target_os = "horizon")]
+pub use self::launch::launch;
 use self::{
+    backend::{Backend, C2dImageTrait, MeasureText, Target},
     image_data_set::ImageDataSet,
-    rdom::{rdom_style::RdomStyle, taffy_layout::TaffyLayout},
-    revent::{EventTrigger, SerializedHtmlEventConverter},
+    pattern::Pattern,
+    rdom::{
+        rdom_style::{Eye, RdomStyle},
+        taffy_layout::TaffyLayout,
+    },
+    revent::SerializedHtmlEventConverter,
 };
 
+pub mod backend;
+pub mod frame;
 pub mod image_data_set;
+#[cfg(target_os = "horizon")]
+mod launch;
+mod pattern;
 mod rdom;
 pub mod revent;
+#[cfg(test)]
+pub mod snapshot;
+pub mod software;
+pub mod tween;
+
+// the screens to draw again, the other one keeps its last frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Screens {
+    pub top: bool,
+    pub bottom: bool,
+}
+
+impl Screens {
+    pub const ALL: Screens = Screens {
+        top: true,
+        bottom: true,
+    };
+    pub const TOP: Screens = Screens {
+        top: true,
+        bottom: false,
+    };
+
+    pub fn is_empty(&self) -> bool {
+        !self.top && !self.bottom
+    }
+
+    pub fn contains(&self, is_top_screen: bool) -> bool {
+        if is_top_screen {
+            self.top
+        } else {
+            self.bottom
+        }
+    }
+
+    pub fn mark(&mut self, is_top_screen: bool) {
+        if is_top_screen {
+            self.top = true;
+        } else {
+            self.bottom = true;
+        }
+    }
+
+    pub fn union(self, other: Screens) -> Screens {
+        Screens {
+            top: self.top || other.top,
+            bottom: self.bottom || other.bottom,
+        }
+    }
+}
+
+// the screen and the box on it of every node, from the last layout
+type NodeBoxes = HashMap<NodeId, (bool, [f32; 4])>;
+
+fn collect_boxes(
+    node: NodeRef,
+    taffy: &TaffyTree<()>,
+    parent_location: Point<f32>,
+    boxes: &mut NodeBoxes,
+) {
+    let Some(layout) = node
+        .get::<TaffyLayout>()
+        .and_then(|state| state.node)
+        .and_then(|taffy_node| taffy.layout(taffy_node).ok())
+    else {
+        return;
+    };
+    let location = Point {
+        x: layout.location.x + parent_location.x,
+        y: layout.location.y + parent_location.y,
+    };
+    let is_top_screen = node.get::<RdomStyle>().is_none_or(|style| style.is_top());
+    boxes.insert(
+        node.id(),
+        (
+            is_top_screen,
+            [
+                location.x,
+                location.y,
+                layout.size.width,
+                layout.size.height,
+            ],
+        ),
+    );
+    let rdom = node.real_dom();
+    for child_id in rdom.tree_ref().children_ids_advanced(node.id(), true) {
+        collect_boxes(rdom.get(child_id).unwrap(), taffy, location, boxes);
+    }
+}
+
+// the alpha scaled by the opacity, images and patterns stay opaque
+fn fade(color: u32, opacity: f32) -> u32 {
+    if opacity >= 1.0 {
+        return color;
+    }
+    let alpha = ((color >> 24) as f32 * opacity).round() as u32;
+    (color & 0x00ff_ffff) | (alpha << 24)
+}
 
 fn render(
     node: NodeRef,
     taffy: Arc<Mutex<TaffyTree<()>>>,
     parent_location: Point<f32>,
-    resource: &Rc<Resource>,
+    backend: &dyn Backend,
     current_3d: f32,
+    screens: Screens,
     images: &mut ImageDataSet,
 ) {
     let (origin_x, y, width, height) = {
@@ -62,69 +165,66 @@ fn render(
         scale,
         scale_placeholder,
         deep_3d,
+        eye,
         z_index,
         max_width,
+        opacity,
         ..
     } = rdom_style;
+    let color = fade(color, opacity);
+    let background_color = background_color.map(|color| fade(color, opacity));
+    // the children can be on the other screen, they are walked anyway
+    let is_dirty = screens.contains(is_top_screen);
     let render_3d = is_top_screen && current_3d != 0.0;
+    // nodes for one eye only, to check the crosstalk between the two images
+    let render_left = is_dirty && (!is_top_screen || eye != Eye::Right);
+    let render_right = is_dirty && render_3d && eye != Eye::Left;
     let deep_3d = deep_3d * current_3d;
     let x = if !render_3d {
         origin_x
     } else {
         origin_x - deep_3d
     };
+    let right_x = origin_x + deep_3d;
+    let left_target = if is_top_screen {
+        Target::TopLeft
+    } else {
+        Target::Bottom
+    };
 
     // clear the screen
-    if let Some(color) = reset_color {
-        if is_top_screen {
-            resource.c2d.clear_top_scene(color);
-            resource.c2d.start_top_scene();
-            if render_3d {
-                resource.c2d.clear_top_scene_right(color);
-                resource.c2d.start_top_scene_right();
-            }
-        } else {
-            resource.c2d.clear_bottom_scene(color);
-            resource.c2d.start_bottom_scene();
+    if let Some(color) = reset_color.filter(|_| is_dirty) {
+        backend.clear_scene(left_target, color);
+        backend.start_scene(left_target);
+        if render_3d {
+            backend.clear_scene(Target::TopRight, color);
+            backend.start_scene(Target::TopRight);
         }
     }
 
     // draw element background_color
     if let Some(color) = background_color {
-        if is_top_screen {
-            resource.c2d.start_top_scene();
-        } else {
-            resource.c2d.start_bottom_scene();
+        if render_left {
+            backend.start_scene(left_target);
+            backend.draw_rect(x, y, z_index, width, height, color);
         }
-        c2d_draw_rect(x, y, z_index, width, height, color);
 
-        if render_3d {
-            resource.c2d.start_top_scene_right();
-            c2d_draw_rect(origin_x + deep_3d, y, z_index, width, height, color);
+        if render_right {
+            backend.start_scene(Target::TopRight);
+            backend.draw_rect(right_x, y, z_index, width, height, color);
         }
     }
 
     match &*node.node_type() {
         NodeType::Text(text) => {
-            if is_top_screen {
-                resource.c2d.start_top_scene();
-            } else {
-                resource.c2d.start_bottom_scene();
-            }
-            let c2d_text = C2dText::new(&text.text);
-            if let Some(max_width) = max_width {
-                c2d_draw_text_wrap(&c2d_text, x, y, z_index, scale, color, max_width);
-            } else {
-                c2d_draw_text(&c2d_text, x, y, z_index, scale, color);
+            if render_left {
+                backend.start_scene(left_target);
+                backend.draw_text(&text.text, x, y, z_index, scale, color, max_width);
             }
 
-            if render_3d {
-                resource.c2d.start_top_scene_right();
-                if let Some(max_width) = max_width {
-                    c2d_draw_text_wrap(&c2d_text, x, y, z_index, scale, color, max_width);
-                } else {
-                    c2d_draw_text(&c2d_text, origin_x + deep_3d, y, z_index, scale, color);
-                }
+            if render_right {
+                backend.start_scene(Target::TopRight);
+                backend.draw_text(&text.text, right_x, y, z_index, scale, color, max_width);
             }
         }
         NodeType::Element(ElementNode {
@@ -134,24 +234,56 @@ fn render(
                 "div" => {
                     // border ?
                 }
+                "canvas" => {
+                    if let Some(pattern) = Pattern::from_attributes(attributes) {
+                        // whole pixels, so 1px patterns light exactly one pixel column or row
+                        let (y, width, height) = (y.round(), width.round(), height.round());
+                        if render_left {
+                            backend.start_scene(left_target);
+                            pattern.draw(backend, x.round(), y, z_index, width, height);
+                        }
+
+                        if render_right {
+                            backend.start_scene(Target::TopRight);
+                            pattern.draw(backend, right_x.round(), y, z_index, width, height);
+                        }
+                    }
+                }
                 "img" => {
-                    let (image, is_placeholder): (Option<Box<Rc<dyn C2dImageTrait>>>, bool) =
+                    let (image, is_placeholder): (Option<Rc<dyn C2dImageTrait + '_>>, bool) =
                         match match attributes
                             .get(&OwnedAttributeDiscription::from("src".to_string()))
                         {
-                            Some(OwnedAttributeValue::Int(idx)) => resource
-                                .c2d
-                                .get_image_from_sheet(*idx as usize)
-                                .map(|image| Box::new(Rc::new(image) as Rc<dyn C2dImageTrait>)),
+                            Some(OwnedAttributeValue::Int(idx)) => {
+                                backend.sheet_image(*idx as usize)
+                            }
                             Some(OwnedAttributeValue::Text(id)) => {
                                 match attributes
                                     .get(&OwnedAttributeDiscription::from("media".to_string()))
                                 {
                                     Some(OwnedAttributeValue::Text(media)) => {
-                                        if media == "qrcode" {
-                                            images.get_qrcode(id)
-                                        } else {
-                                            images.get_image(id)
+                                        match media.as_str() {
+                                            "qrcode" => {
+                                                images.get_qrcode(backend, id).map(|image| *image)
+                                            }
+                                            // a png or bmp path, stretched to the element
+                                            "file" => images
+                                                .get_file_image(
+                                                    backend,
+                                                    id,
+                                                    width as u32,
+                                                    height as u32,
+                                                    matches!(
+                                                        attributes.get(
+                                                            &OwnedAttributeDiscription::from(
+                                                                "invert".to_string()
+                                                            )
+                                                        ),
+                                                        Some(OwnedAttributeValue::Bool(true))
+                                                    ),
+                                                )
+                                                .map(|image| *image),
+                                            _ => images.get_image(backend, id).map(|image| *image),
                                         }
                                     }
                                     _ => None,
@@ -160,38 +292,23 @@ fn render(
                             _ => None,
                         } {
                             Some(image) => (Some(image), false),
-                            None => (
-                                resource
-                                    .c2d
-                                    .get_image_from_sheet(4)
-                                    .map(|image| Box::new(Rc::new(image) as Rc<dyn C2dImageTrait>)),
-                                true,
-                            ),
+                            None => (backend.sheet_image(4), true),
                         };
 
                     if let Some(image) = image {
-                        if is_top_screen {
-                            resource.c2d.start_top_scene();
-                        } else {
-                            resource.c2d.start_bottom_scene();
-                        }
                         let scale = if is_placeholder {
                             scale_placeholder.unwrap_or(scale)
                         } else {
                             scale
                         };
-                        c2d_draw_image(image.get_image(), x, y, z_index, scale, scale);
-
-                        if render_3d {
-                            resource.c2d.start_top_scene_right();
-                            c2d_draw_image(
-                                image.get_image(),
-                                origin_x + deep_3d,
-                                y,
-                                z_index,
-                                scale,
-                                scale,
-                            );
+                        if render_left {
+                            backend.start_scene(left_target);
+                            backend.draw_image(image.as_ref(), x, y, z_index, scale);
+                        }
+
+                        if render_right {
+                            backend.start_scene(Target::TopRight);
+                            backend.draw_image(image.as_ref(), right_x, y, z_index, scale);
                         }
                     }
                 }
@@ -204,8 +321,9 @@ fn render(
                     child,
                     Arc::clone(&taffy),
                     Point { x: origin_x, y },
-                    resource,
+                    backend,
                     current_3d,
+                    screens,
                     images,
                 );
             }
@@ -249,119 +367,149 @@ fn compute_layout(taffy: &Arc<Mutex<TaffyTree<()>>>, rdom: &RealDom) {
         .unwrap();
 }
 
-pub fn launch(
-    app_enter: fn() -> Element,
-    resource: Rc<Resource>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // we need to run the vdom in a async runtime
-    tokio::runtime::Builder::new_multi_thread()
-        .worker_threads(2)
-        .enable_time()
-        .build()?
-        .block_on(async move {
-            // set event converter
-            dioxus_html::set_event_converter(Box::new(SerializedHtmlEventConverter));
-            // get the current 3d slider state
-            let mut current_3d = 2.0;
-            // taffy tree
-            let taffy: Arc<Mutex<TaffyTree<()>>> = Arc::new(Mutex::new(TaffyTree::new()));
-            // create the vdom, the real_dom, and the binding layer between them
-            // provide the app data to the vdom
-            let mut vdom = VirtualDom::new(app_enter).with_root_context(Rc::clone(&resource));
-            // rdom
-            let mut rdom =
-                RealDom::new([TaffyLayout::to_type_erased(), RdomStyle::to_type_erased()]);
-            // create the dioxus state
-            let mut dioxus_state = DioxusState::create(&mut rdom);
-            // rebuild
-            vdom.rebuild(&mut dioxus_state.create_mutation_writer(&mut rdom));
-            // create the context
-            let mut ctx = SendAnyMap::new();
-            // insert taffy
-            ctx.insert(Arc::clone(&taffy));
-            // update the State for nodes in the real_dom tree
-            let to_rerender = rdom.update_state(ctx);
-            let mut is_layout_dirty = !to_rerender.0.is_empty() || !to_rerender.1.is_empty();
-            let mut is_need_rerender = is_layout_dirty;
-            let mut event_trigger = EventTrigger::new();
-            let mut image_data_set = ImageDataSet::new();
-            let mut current_new_3d = current_3d_slider_state();
-            while !AppExit::is_exit() && resource.main_loop() {
-                // update the taffy layout
-                // let now = std::time::Instant::now();
-                if is_layout_dirty {
-                    is_layout_dirty = false;
-                    compute_layout(&taffy, &rdom);
-                }
+// the vdom, the real dom and their layout, what launch runs on the console and
+// what the software backend draws on the host
+pub struct Document {
+    pub vdom: VirtualDom,
+    pub rdom: RealDom,
+    dioxus_state: DioxusState,
+    pub taffy: Arc<Mutex<TaffyTree<()>>>,
+    pub images: ImageDataSet,
+    measure_text: MeasureText,
+    is_layout_dirty: bool,
+    // the screens changed since the last draw
+    dirty: Screens,
+    boxes: NodeBoxes,
+}
 
-                if is_need_rerender {
-                    is_need_rerender = false;
-                    // start render...
-                    resource.c2d.start_drawing();
-                    // if the 3d slider state changed, we need to update the 3d state
-                    if current_3d != current_new_3d {
-                        current_3d = current_new_3d;
-                        if current_3d != 0.0 {
-                            resource.c2d.enable_3d();
-                        } else {
-                            resource.c2d.disable_3d();
-                        }
-                    }
-                    // render the real_dom tree
-                    render(
-                        rdom.get(rdom.root_id()).unwrap(),
-                        Arc::clone(&taffy),
-                        Point { x: 0.0, y: 0.0 },
-                        &resource,
-                        current_3d,
-                        &mut image_data_set,
-                    );
-                    // end render...
-                    resource.c2d.end_drawing();
-                    // Loading missing images
-                    image_data_set.loading_missing_image();
-                    // release qrcode
-                    image_data_set.release_qrcode();
-                }
+impl Document {
+    pub fn new(mut vdom: VirtualDom, measure_text: MeasureText) -> Self {
+        // set event converter
+        dioxus_html::set_event_converter(Box::new(SerializedHtmlEventConverter));
+        // rdom
+        let mut rdom = RealDom::new([TaffyLayout::to_type_erased(), RdomStyle::to_type_erased()]);
+        // create the dioxus state
+        let mut dioxus_state = DioxusState::create(&mut rdom);
+        // rebuild
+        vdom.rebuild(&mut dioxus_state.create_mutation_writer(&mut rdom));
+        let mut document = Self {
+            vdom,
+            rdom,
+            dioxus_state,
+            taffy: Arc::new(Mutex::new(TaffyTree::new())),
+            images: ImageDataSet::new(),
+            measure_text,
+            is_layout_dirty: false,
+            dirty: Screens::ALL,
+            boxes: HashMap::new(),
+        };
+        document.update_state();
+        document
+    }
 
-                // println!("render time: {:?}", now.elapsed());
+    // update the State for nodes in the real_dom tree, true if it changed
+    fn update_state(&mut self) -> bool {
+        let mut ctx = SendAnyMap::new();
+        // insert taffy
+        ctx.insert(Arc::clone(&self.taffy));
+        ctx.insert(self.measure_text);
+        let (to_rerender, changed) = self.rdom.update_state(ctx);
+        // the screen a node was on and the one it is on now, removed nodes only have the first
+        let ids = to_rerender
+            .iter()
+            .map(|id| *id)
+            .chain(changed.keys().copied());
+        for id in ids {
+            if let Some((is_top_screen, _)) = self.boxes.get(&id) {
+                self.dirty.mark(*is_top_screen);
+            }
+            let is_top_screen = self.rdom.get(id).and_then(|node| {
+                let style = node.get::<RdomStyle>()?;
+                Some(style.is_top())
+            });
+            if let Some(is_top_screen) = is_top_screen {
+                self.dirty.mark(is_top_screen);
+            }
+        }
+        let is_changed = !to_rerender.is_empty() || !changed.is_empty();
+        self.is_layout_dirty |= is_changed;
+        is_changed
+    }
 
-                while resource.main_loop() {
-                    tokio::select! {
-                        _ = async {
-                            if is_need_rerender {
-                                // combine multiple rerenders into one
-                                sleep_micros(100).await
-                            } else {
-                                sleep_micros_for_ever(1000000).await
-                            }
-                        } => {
-                            break;
-                        }
-                        _ = event_trigger.poll_event_and_wait_for_work(
-                            &resource,
-                            &rdom,
-                            &mut vdom,
-                            &current_3d,
-                            &mut current_new_3d,
-                            &mut image_data_set,
-                            taffy.clone(),
-                        ) => {
-                            is_need_rerender = true;
-                        }
-                    }
+    // for changes outside the real dom, the 3d slider or images loaded
+    pub fn invalidate(&mut self, screens: Screens) {
+        self.dirty = self.dirty.union(screens);
+    }
 
-                    // get the mutations from the vdom and apply them to the real_dom
-                    vdom.render_immediate(&mut dioxus_state.create_mutation_writer(&mut rdom));
-                    let mut ctx = SendAnyMap::new();
-                    ctx.insert(Arc::clone(&taffy));
-                    let to_rerender = rdom.update_state(ctx);
-                    if !to_rerender.0.is_empty() || !to_rerender.1.is_empty() {
-                        is_layout_dirty = true;
-                    }
+    // the nodes moved by the layout dirty the screens they left and the ones they are on
+    fn update_boxes(&mut self) {
+        let mut boxes = NodeBoxes::new();
+        {
+            let taffy = self.taffy.lock().expect("get taffy lock in update boxes");
+            collect_boxes(
+                self.rdom.get(self.rdom.root_id()).unwrap(),
+                &taffy,
+                Point { x: 0.0, y: 0.0 },
+                &mut boxes,
+            );
+        }
+        for (id, &(is_top_screen, rect)) in &boxes {
+            match self.boxes.get(id) {
+                Some(&(was_top_screen, old_rect)) if old_rect != rect => {
+                    self.dirty.mark(was_top_screen);
+                    self.dirty.mark(is_top_screen);
                 }
+                None => self.dirty.mark(is_top_screen),
+                _ => {}
+            }
+        }
+        for (id, &(was_top_screen, _)) in &self.boxes {
+            if !boxes.contains_key(id) {
+                self.dirty.mark(was_top_screen);
             }
-        });
+        }
+        self.boxes = boxes;
+    }
 
-    Ok(())
+    // get the mutations from the vdom and apply them to the real_dom
+    pub fn apply_mutations(&mut self) -> bool {
+        self.vdom
+            .render_immediate(&mut self.dioxus_state.create_mutation_writer(&mut self.rdom));
+        self.update_state()
+    }
+
+    // the screens drawn, nothing is drawn when no screen changed
+    pub fn draw(&mut self, backend: &dyn Backend, current_3d: f32) -> Screens {
+        // update the taffy layout
+        if self.is_layout_dirty {
+            self.is_layout_dirty = false;
+            compute_layout(&self.taffy, &self.rdom);
+            self.update_boxes();
+        }
+        let screens = std::mem::take(&mut self.dirty);
+        if screens.is_empty() {
+            return screens;
+        }
+        // start render...
+        backend.start_drawing();
+        // render the real_dom tree
+        render(
+            self.rdom.get(self.rdom.root_id()).unwrap(),
+            Arc::clone(&self.taffy),
+            Point { x: 0.0, y: 0.0 },
+            backend,
+            current_3d,
+            screens,
+            &mut self.images,
+        );
+        // end render...
+        backend.end_drawing();
+        // Loading missing images
+        self.images.loading_missing_image();
+        // release qrcode
+        self.images.release_qrcode();
+        // release sd card images that are not shown anymore
+        self.images.release_files();
+        screens
+    }
 }
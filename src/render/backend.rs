@@ -0,0 +1,87 @@
+//! what render draws with, the C2D functions on the console and the
+//! software rasterizer on the host
+use std::rc::Rc;
+
+#[cfg(target_os = "horizon")]
+use crate::c2d::C2dImage;
+use crate::image_file::RgbaImage;
+
+#[cfg(target_os = "horizon")]
+mod ctr;
+
+// the three render targets, the top screen has one per eye
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    TopLeft,
+    TopRight,
+    Bottom,
+}
+
+impl Target {
+    pub const ALL: [Target; 3] = [Target::TopLeft, Target::TopRight, Target::Bottom];
+}
+
+// (width, height) of a text at a scale, the layout runs off the render loop so
+// it can not borrow the backend
+#[derive(Clone, Copy)]
+pub struct MeasureText(pub fn(&str, f32) -> (f32, f32));
+
+// an image a backend loaded, each backend draws only its own kind
+pub trait C2dImageTrait {
+    #[cfg(target_os = "horizon")]
+    fn get_image(&self) -> Option<&C2dImage> {
+        None
+    }
+
+    // only the software backend can read the pixels back
+    fn get_pixels(&self) -> Option<&RgbaImage> {
+        None
+    }
+}
+
+pub trait Backend {
+    fn start_drawing(&self);
+    fn end_drawing(&self);
+    // the target the next draw calls go to
+    fn start_scene(&self, target: Target);
+    fn clear_scene(&self, target: Target, color: u32);
+    fn set_3d(&self, enabled: bool);
+
+    fn draw_rect(&self, x: f32, y: f32, z: f32, w: f32, h: f32, color: u32);
+    // corner colors are top left, top right, bottom left, bottom right
+    fn draw_rect_gradient(&self, x: f32, y: f32, z: f32, w: f32, h: f32, colors: [u32; 4]);
+    #[allow(clippy::too_many_arguments)]
+    fn draw_line(
+        &self,
+        x0: f32,
+        y0: f32,
+        color0: u32,
+        x1: f32,
+        y1: f32,
+        color1: u32,
+        width: f32,
+        z: f32,
+    );
+    // wrapped at words when max_width is set
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text(
+        &self,
+        text: &str,
+        x: f32,
+        y: f32,
+        z: f32,
+        scale: f32,
+        color: u32,
+        max_width: Option<f32>,
+    );
+    fn measure_text(&self) -> MeasureText;
+    fn draw_image(&self, image: &dyn C2dImageTrait, x: f32, y: f32, z: f32, scale: f32);
+
+    // images from the asset sheet
+    fn sheet_image(&self, idx: usize) -> Option<Rc<dyn C2dImageTrait + '_>>;
+    fn load_rgba_image(&self, image: RgbaImage) -> Option<Rc<dyn C2dImageTrait>>;
+    // a 48x48 smdh icon, rgb565 in 8x8 tiles
+    fn load_icon(&self, buffer: &[u16]) -> Option<Rc<dyn C2dImageTrait>>;
+    // a square of luma bytes from qrcode_generator
+    fn load_qrcode(&self, buffer: &[u8]) -> Option<Rc<dyn C2dImageTrait>>;
+}
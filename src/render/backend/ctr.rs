@@ -0,0 +1,117 @@
+use std::rc::Rc;
+
+use crate::{
+    c2d::{
+        c2d_draw_image, c2d_draw_line, c2d_draw_rect, c2d_draw_rect_gradient, c2d_draw_text,
+        c2d_draw_text_wrap, c2d_load_icon_from_buffer, c2d_load_qrcode_from_buffer,
+        c2d_load_rgba_image, C2dText, C2D,
+    },
+    image_file::RgbaImage,
+};
+
+use super::{Backend, C2dImageTrait, MeasureText, Target};
+
+fn c2d_measure_text(text: &str, scale: f32) -> (f32, f32) {
+    C2dText::new(text).dimension(scale, scale)
+}
+
+impl Backend for C2D {
+    fn start_drawing(&self) {
+        C2D::start_drawing(self);
+    }
+
+    fn end_drawing(&self) {
+        C2D::end_drawing(self);
+    }
+
+    fn start_scene(&self, target: Target) {
+        match target {
+            Target::TopLeft => self.start_top_scene_left(),
+            Target::TopRight => self.start_top_scene_right(),
+            Target::Bottom => self.start_bottom_scene(),
+        }
+    }
+
+    fn clear_scene(&self, target: Target, color: u32) {
+        match target {
+            Target::TopLeft => self.clear_top_scene_left(color),
+            Target::TopRight => self.clear_top_scene_right(color),
+            Target::Bottom => self.clear_bottom_scene(color),
+        }
+    }
+
+    fn set_3d(&self, enabled: bool) {
+        if enabled {
+            self.enable_3d();
+        } else {
+            self.disable_3d();
+        }
+    }
+
+    fn draw_rect(&self, x: f32, y: f32, z: f32, w: f32, h: f32, color: u32) {
+        c2d_draw_rect(x, y, z, w, h, color);
+    }
+
+    fn draw_rect_gradient(&self, x: f32, y: f32, z: f32, w: f32, h: f32, colors: [u32; 4]) {
+        c2d_draw_rect_gradient(x, y, z, w, h, colors);
+    }
+
+    fn draw_line(
+        &self,
+        x0: f32,
+        y0: f32,
+        color0: u32,
+        x1: f32,
+        y1: f32,
+        color1: u32,
+        width: f32,
+        z: f32,
+    ) {
+        c2d_draw_line(x0, y0, color0, x1, y1, color1, width, z);
+    }
+
+    fn draw_text(
+        &self,
+        text: &str,
+        x: f32,
+        y: f32,
+        z: f32,
+        scale: f32,
+        color: u32,
+        max_width: Option<f32>,
+    ) {
+        let text = C2dText::new(text);
+        match max_width {
+            Some(max_width) => c2d_draw_text_wrap(&text, x, y, z, scale, color, max_width),
+            None => c2d_draw_text(&text, x, y, z, scale, color),
+        }
+    }
+
+    fn measure_text(&self) -> MeasureText {
+        MeasureText(c2d_measure_text)
+    }
+
+    fn draw_image(&self, image: &dyn C2dImageTrait, x: f32, y: f32, z: f32, scale: f32) {
+        if let Some(image) = image.get_image() {
+            c2d_draw_image(image, x, y, z, scale, scale);
+        }
+    }
+
+    fn sheet_image(&self, idx: usize) -> Option<Rc<dyn C2dImageTrait + '_>> {
+        self.get_image_from_sheet(idx)
+            .map(|image| Rc::new(image) as Rc<dyn C2dImageTrait + '_>)
+    }
+
+    fn load_rgba_image(&self, image: RgbaImage) -> Option<Rc<dyn C2dImageTrait>> {
+        c2d_load_rgba_image(&image.pixels, image.width, image.height)
+            .map(|image| Rc::new(image) as Rc<dyn C2dImageTrait>)
+    }
+
+    fn load_icon(&self, buffer: &[u16]) -> Option<Rc<dyn C2dImageTrait>> {
+        Some(Rc::new(c2d_load_icon_from_buffer(buffer)))
+    }
+
+    fn load_qrcode(&self, buffer: &[u8]) -> Option<Rc<dyn C2dImageTrait>> {
+        Some(Rc::new(c2d_load_qrcode_from_buffer(buffer)))
+    }
+}
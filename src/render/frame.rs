@@ -0,0 +1,154 @@
+//! frames for animations, launch ticks them after the vblank while a component
+//! listens through use_animation_frame, and sleeps otherwise
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use dioxus::prelude::*;
+// follows the paused clock of the snapshot tests
+use tokio::time::Instant;
+
+#[derive(Default)]
+struct FrameState {
+    // frames ticked so far
+    count: u64,
+    subscribers: usize,
+    wakers: Vec<Waker>,
+}
+
+// the frames of one VirtualDom, launch and the snapshot harness provide it as a root context
+#[derive(Clone, Default)]
+pub struct AnimationFrames(Rc<RefCell<FrameState>>);
+
+impl AnimationFrames {
+    // frames ticked so far
+    pub fn count(&self) -> u64 {
+        self.0.borrow().count
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.0.borrow().subscribers > 0
+    }
+
+    // wake everything waiting for the next frame
+    pub fn tick(&self) {
+        let wakers = {
+            let mut frames = self.0.borrow_mut();
+            frames.count += 1;
+            std::mem::take(&mut frames.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    pub fn next(&self) -> NextFrame {
+        NextFrame {
+            frames: self.clone(),
+            after: self.count(),
+        }
+    }
+
+    // counted while alive, launch only ticks when there is one
+    pub(crate) fn subscribe(&self) -> Subscription {
+        self.0.borrow_mut().subscribers += 1;
+        Subscription {
+            frames: self.clone(),
+        }
+    }
+}
+
+pub struct NextFrame {
+    frames: AnimationFrames,
+    after: u64,
+}
+
+impl Future for NextFrame {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut frames = self.frames.0.borrow_mut();
+        if frames.count > self.after {
+            return Poll::Ready(());
+        }
+        frames.wakers.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+pub(crate) struct Subscription {
+    frames: AnimationFrames,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.frames.0.borrow_mut().subscribers -= 1;
+    }
+}
+
+// the frames on a fixed period, in phase with the last vblank waited for
+pub struct FrameClock {
+    period: Duration,
+    synced_at: Instant,
+}
+
+impl FrameClock {
+    pub fn new(period_micros: u64) -> Self {
+        Self {
+            period: Duration::from_micros(period_micros),
+            synced_at: Instant::now(),
+        }
+    }
+
+    // drawing waits for the vblank, so right after is the start of a frame
+    pub fn sync(&mut self) {
+        self.synced_at = Instant::now();
+    }
+
+    pub fn until_next(&self) -> Duration {
+        self.until_next_after(self.synced_at.elapsed())
+    }
+
+    fn until_next_after(&self, elapsed: Duration) -> Duration {
+        let period = self.period.as_micros().max(1);
+        Duration::from_micros((period - elapsed.as_micros() % period) as u64)
+    }
+}
+
+// calls `on_frame` with the time since the previous call on every frame while the
+// component is mounted, only the callback of the first render is kept
+pub fn use_animation_frame(on_frame: impl FnMut(Duration) + 'static) {
+    let frames = use_context::<AnimationFrames>();
+    let on_frame = use_hook(|| Rc::new(RefCell::new(on_frame)));
+    use_future(move || {
+        let on_frame = Rc::clone(&on_frame);
+        let frames = frames.clone();
+        async move {
+            let _subscription = frames.subscribe();
+            let mut last_at = Instant::now();
+            loop {
+                frames.next().await;
+                let now = Instant::now();
+                (on_frame.borrow_mut())(now - last_at);
+                last_at = now;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_clock_stays_in_phase() {
+        let clock = FrameClock::new(16_666);
+        let next = |micros| clock.until_next_after(Duration::from_micros(micros));
+        assert_eq!(next(0), Duration::from_micros(16_666));
+        assert_eq!(next(20_000), Duration::from_micros(13_332));
+        assert_eq!(next(33_332), Duration::from_micros(16_666));
+    }
+}
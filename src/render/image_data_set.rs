@@ -1,10 +1,14 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     rc::Rc,
-    sync::{Arc, OnceLock, RwLock},
+    sync::{Arc, Mutex, OnceLock, RwLock},
 };
 
-use crate::c2d::{c2d_load_icon_from_buffer, c2d_load_qrcode_from_buffer, C2dImage, C2dImageTrait};
+use log::warn;
+
+use crate::image_file::RgbaImage;
+
+use super::backend::{Backend, C2dImageTrait};
 
 static IMAGE_RAW_BUFS: OnceLock<RwLock<HashMap<u64, Vec<u16>>>> = OnceLock::new();
 
@@ -12,11 +16,21 @@ pub fn get_image_raw_buf() -> &'static RwLock<HashMap<u64, Vec<u16>>> {
     IMAGE_RAW_BUFS.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
+// icons by title id, the buffer is filled in off the render loop
+type PendingIcons = Arc<RwLock<Vec<(u64, Option<Vec<u16>>)>>>;
+// sd card images decoded since the last frame
+type LoadedFiles = Arc<Mutex<Vec<(String, Option<RgbaImage>)>>>;
+
 pub struct ImageDataSet {
-    data: HashMap<u64, Option<Rc<C2dImage>>>,
-    data_pending: Arc<RwLock<Vec<(u64, Option<Vec<u16>>)>>>,
+    data: HashMap<u64, Option<Rc<dyn C2dImageTrait>>>,
+    data_pending: PendingIcons,
     // (use count, data)
-    qrcode: (u16, HashMap<String, Rc<C2dImage>>),
+    qrcode: (u16, HashMap<String, Rc<dyn C2dImageTrait>>),
+    // sd card images by "path|widthxheight|invert", None if it could not be loaded
+    files: HashMap<String, Option<Rc<dyn C2dImageTrait>>>,
+    files_used: HashSet<String>,
+    files_loading: HashSet<String>,
+    files_loaded: LoadedFiles,
     need_update: Arc<RwLock<bool>>,
 }
 
@@ -26,6 +40,10 @@ impl ImageDataSet {
             data: HashMap::new(),
             data_pending: Arc::new(RwLock::new(vec![])),
             qrcode: (0, HashMap::new()),
+            files: HashMap::new(),
+            files_used: HashSet::new(),
+            files_loading: HashSet::new(),
+            files_loaded: Arc::new(Mutex::new(vec![])),
             need_update: Arc::new(RwLock::new(false)),
         }
     }
@@ -41,20 +59,24 @@ impl ImageDataSet {
         false
     }
 
-    pub fn add_qrcode(&mut self, id: String, image: Rc<C2dImage>) {
+    pub fn add_qrcode(&mut self, id: String, image: Rc<dyn C2dImageTrait>) {
         self.qrcode.1.insert(id, image);
     }
 
-    pub fn get_qrcode(&mut self, id: &str) -> Option<Box<Rc<dyn C2dImageTrait>>> {
+    pub fn get_qrcode(
+        &mut self,
+        backend: &dyn Backend,
+        id: &str,
+    ) -> Option<Box<Rc<dyn C2dImageTrait>>> {
         self.qrcode.0 += 1;
         if let Some(image) = self.qrcode.1.get(id) {
             Some(Box::new(image.clone()))
         } else if let Ok(buf) =
             qrcode_generator::to_image(id, qrcode_generator::QrCodeEcc::Low, 128)
         {
-            let image = Rc::new(c2d_load_qrcode_from_buffer(&buf));
+            let image = backend.load_qrcode(&buf)?;
             self.add_qrcode(id.to_string(), Rc::clone(&image));
-            Some(Box::new(image as Rc<dyn C2dImageTrait>))
+            Some(Box::new(image))
         } else {
             None
         }
@@ -67,6 +89,67 @@ impl ImageDataSet {
         self.qrcode.0 = 0;
     }
 
+    // decoded off the render loop, the first calls return None until it is ready
+    pub fn get_file_image(
+        &mut self,
+        backend: &dyn Backend,
+        path: &str,
+        width: u32,
+        height: u32,
+        invert: bool,
+    ) -> Option<Box<Rc<dyn C2dImageTrait>>> {
+        let key = format!("{}|{}x{}|{}", path, width, height, invert);
+        self.files_used.insert(key.clone());
+
+        // upload the images decoded since the last frame
+        if let Ok(mut loaded) = self.files_loaded.lock() {
+            for (key, image) in loaded.drain(..) {
+                self.files_loading.remove(&key);
+                let image = image.and_then(|image| backend.load_rgba_image(image));
+                self.files.insert(key, image);
+            }
+        }
+
+        if let Some(image) = self.files.get(&key) {
+            return image.as_ref().map(|image| Box::new(Rc::clone(image)));
+        }
+
+        if width > 0 && height > 0 && self.files_loading.insert(key.clone()) {
+            let path = path.to_string();
+            let loaded = Arc::clone(&self.files_loaded);
+            let need_update = Arc::clone(&self.need_update);
+            tokio::task::spawn_blocking(move || {
+                let image = match RgbaImage::load(&path) {
+                    Ok(image) => {
+                        let mut image = image.resize(width, height);
+                        if invert {
+                            image.invert();
+                        }
+                        Some(image)
+                    }
+                    Err(err) => {
+                        warn!("failed to load image {}: {}", path, err);
+                        None
+                    }
+                };
+                if let Ok(mut loaded) = loaded.lock() {
+                    loaded.push((key, image));
+                }
+                // trigger update
+                if let Ok(mut need_update) = need_update.write() {
+                    *need_update = true;
+                }
+            });
+        }
+        None
+    }
+
+    // keep only the sd card images drawn in the last frame
+    pub fn release_files(&mut self) {
+        let used = std::mem::take(&mut self.files_used);
+        self.files.retain(|key, _| used.contains(key));
+    }
+
     pub fn loading_missing_image(&mut self) {
         if Arc::strong_count(&self.data_pending) > 1 {
             return;
@@ -122,19 +205,22 @@ impl ImageDataSet {
         });
     }
 
-    pub fn get_image(&mut self, id: &str) -> Option<Box<Rc<dyn C2dImageTrait>>> {
+    pub fn get_image(
+        &mut self,
+        backend: &dyn Backend,
+        id: &str,
+    ) -> Option<Box<Rc<dyn C2dImageTrait>>> {
         if let Ok(id) = id.parse::<u64>() {
             if let Some(item) = self.data.get(&id) {
-                item.as_ref()
-                    .map(|image| Box::new(image.clone() as Rc<dyn C2dImageTrait>))
+                item.as_ref().map(|image| Box::new(Rc::clone(image)))
             } else {
                 let mut find_img = (false, None);
                 {
                     if let Ok(mut m) = get_image_raw_buf().write() {
                         if let Some(buf) = m.remove(&id) {
-                            let image = Rc::new(c2d_load_icon_from_buffer(&buf));
-                            self.data.insert(id, Some(image.clone()));
-                            find_img = (true, Some(Box::new(image as Rc<dyn C2dImageTrait>)));
+                            let image = backend.load_icon(&buf);
+                            self.data.insert(id, image.clone());
+                            find_img = (true, image.map(Box::new));
                         }
                     }
                 }
@@ -148,17 +234,11 @@ impl ImageDataSet {
                             find_idx = Some(idx);
                             if let Some(buf) = buf {
                                 let item = if !buf.is_empty() {
-                                    Some(Rc::new(c2d_load_icon_from_buffer(buf)))
+                                    backend.load_icon(buf)
                                 } else {
                                     None
                                 };
-                                find_img = match &item {
-                                    Some(item) => (
-                                        true,
-                                        Some(Box::new(Rc::clone(item) as Rc<dyn C2dImageTrait>)),
-                                    ),
-                                    None => (true, None),
-                                };
+                                find_img = (true, item.clone().map(Box::new));
                                 self.data.insert(id, item);
                             }
                             break;
@@ -179,3 +259,9 @@ impl ImageDataSet {
         }
     }
 }
+
+impl Default for ImageDataSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,108 @@
+//! runs the app on the console, draws with citro2d and reads the controller
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use crate::{
+    app::{App3d, AppExit},
+    platform::current_3d_slider_state,
+    resource::Resource,
+    utils::{get_frame_time, sleep_micros, sleep_micros_for_ever},
+};
+
+use super::{
+    backend::Backend,
+    frame::{AnimationFrames, FrameClock},
+    revent::EventTrigger,
+    Document, Screens,
+};
+
+pub fn launch(
+    app_enter: fn() -> Element,
+    resource: Rc<Resource>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // we need to run the vdom in a async runtime
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()?
+        .block_on(async move {
+            let backend: &dyn Backend = resource.c2d.as_ref();
+            // get the current 3d slider state
+            let mut current_3d = 2.0;
+            // create the vdom, the real_dom, and the binding layer between them
+            // provide the app data to the vdom
+            let frames = AnimationFrames::default();
+            let vdom = VirtualDom::new(app_enter)
+                .with_root_context(Rc::clone(&resource))
+                .with_root_context(frames.clone());
+            let mut document = Document::new(vdom, backend.measure_text());
+            let mut is_need_rerender = true;
+            let mut event_trigger = EventTrigger::new();
+            let mut frame_clock = FrameClock::new(get_frame_time());
+            let mut current_new_3d = if App3d::is_enabled() {
+                current_3d_slider_state()
+            } else {
+                0.0
+            };
+            while !AppExit::is_exit() && resource.main_loop() {
+                // let now = std::time::Instant::now();
+                if is_need_rerender {
+                    is_need_rerender = false;
+                    // if the 3d slider state changed, we need to update the 3d state
+                    if current_3d != current_new_3d {
+                        current_3d = current_new_3d;
+                        App3d::set_slider(current_3d);
+                        backend.set_3d(current_3d != 0.0);
+                        document.invalidate(Screens::TOP);
+                    }
+                    if !document.draw(backend, current_3d).is_empty() {
+                        frame_clock.sync();
+                    }
+                }
+
+                // println!("render time: {:?}", now.elapsed());
+
+                while resource.main_loop() {
+                    tokio::select! {
+                        _ = async {
+                            if is_need_rerender {
+                                // combine multiple rerenders into one
+                                sleep_micros(100).await
+                            } else {
+                                sleep_micros_for_ever(1000000).await
+                            }
+                        } => {
+                            break;
+                        }
+                        // the next frame, only while a component animates
+                        _ = sleep_micros(frame_clock.until_next().as_micros() as u64),
+                            if frames.has_subscribers() => {
+                            frames.tick();
+                        }
+                        // the lid was closed and opened, draw the screens again
+                        _ = resource.wait_for_shell_reopened() => {
+                            document.invalidate(Screens::ALL);
+                            is_need_rerender = true;
+                        }
+                        screens = event_trigger.poll_event_and_wait_for_work(
+                            &resource,
+                            &document.rdom,
+                            &mut document.vdom,
+                            &current_3d,
+                            &mut current_new_3d,
+                            &mut document.images,
+                            document.taffy.clone(),
+                        ) => {
+                            document.invalidate(screens);
+                            is_need_rerender = true;
+                        }
+                    }
+
+                    document.apply_mutations();
+                }
+            }
+        });
+
+    Ok(())
+}
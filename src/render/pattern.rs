@@ -0,0 +1,277 @@
+use std::{collections::HashMap, hash::BuildHasher};
+
+use dioxus_native_core::node::{OwnedAttributeDiscription, OwnedAttributeValue};
+
+use crate::{
+    color::{rgba, Gradient},
+    utils::xorshift32,
+};
+
+use super::backend::Backend;
+
+// patterns drawn by a `canvas` element, e.g.
+// canvas { "pattern": "noise", "seed": 1, "cell": 10 }
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pattern {
+    // random color cells
+    Noise { seed: u32, cell: f32 },
+    // every other cell in `fg`, the rest is left to the background
+    Checkerboard { cell: f32, fg: u32 },
+    // 1px lines every `spacing` pixels
+    Grid { spacing: f32, fg: u32 },
+    // black to white from left to right
+    GrayRamp,
+    // 1px lines through the center
+    Crosshair { fg: u32 },
+    // smooth or stepped, stepped bands can be labelled with their color
+    Gradient { gradient: Gradient, labels: bool },
+    // every `every`th 1px column, or row, in `fg`
+    Lines { every: u32, rows: bool, fg: u32 },
+}
+
+const WHITE: u32 = rgba(0xff, 0xff, 0xff, 0xff);
+const BLACK: u32 = rgba(0x00, 0x00, 0x00, 0xff);
+
+const LABEL_SCALE: f32 = 0.4;
+
+// "r,g,b" in black or white, whichever is readable on the band
+fn draw_band_label(backend: &dyn Backend, x: f32, y: f32, z: f32, w: f32, h: f32, color: u32) {
+    let [r, g, b, _] = color.to_le_bytes();
+    let text = format!("{},{},{}", r, g, b);
+    let (text_w, text_h) = (backend.measure_text().0)(&text, LABEL_SCALE);
+    if text_w > w || text_h > h {
+        return;
+    }
+    let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+    let fg = if luma > 128 { BLACK } else { WHITE };
+    backend.draw_text(
+        &text,
+        x + (w - text_w) / 2.0,
+        y + (h - text_h) / 2.0,
+        z,
+        LABEL_SCALE,
+        fg,
+        None,
+    );
+}
+
+// lines are centered on the coordinate, move them to the middle of the pixel
+fn draw_vline(backend: &dyn Backend, x: f32, y: f32, z: f32, height: f32, color: u32) {
+    backend.draw_line(x + 0.5, y, color, x + 0.5, y + height, color, 1.0, z);
+}
+
+fn draw_hline(backend: &dyn Backend, x: f32, y: f32, z: f32, width: f32, color: u32) {
+    backend.draw_line(x, y + 0.5, color, x + width, y + 0.5, color, 1.0, z);
+}
+
+// one quad with the colors at its corners, or a rect per step
+fn draw_gradient(
+    backend: &dyn Backend,
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+    h: f32,
+    gradient: &Gradient,
+) {
+    let Gradient {
+        start,
+        end,
+        vertical,
+        steps,
+    } = *gradient;
+    if steps == 0 {
+        let colors = if vertical {
+            [start, start, end, end]
+        } else {
+            [start, end, start, end]
+        };
+        backend.draw_rect_gradient(x, y, z, w, h, colors);
+        return;
+    }
+    for (offset, size, color) in gradient.bands(w, h) {
+        if vertical {
+            backend.draw_rect(x, y + offset, z, w, size, color);
+        } else {
+            backend.draw_rect(x + offset, y, z, size, h, color);
+        }
+    }
+}
+
+fn attr<'a, S: BuildHasher>(
+    attributes: &'a HashMap<OwnedAttributeDiscription, OwnedAttributeValue, S>,
+    name: &str,
+) -> Option<&'a OwnedAttributeValue> {
+    attributes.get(&OwnedAttributeDiscription::from(name.to_string()))
+}
+
+fn attr_int<S: BuildHasher>(
+    attributes: &HashMap<OwnedAttributeDiscription, OwnedAttributeValue, S>,
+    name: &str,
+) -> Option<i64> {
+    match attr(attributes, name) {
+        Some(OwnedAttributeValue::Int(value)) => Some(*value),
+        Some(OwnedAttributeValue::Float(value)) => Some(*value as i64),
+        _ => None,
+    }
+}
+
+impl Pattern {
+    pub fn from_attributes<S: BuildHasher>(
+        attributes: &HashMap<OwnedAttributeDiscription, OwnedAttributeValue, S>,
+    ) -> Option<Self> {
+        let name = attr(attributes, "pattern")?.as_text()?;
+        let fg = attr_int(attributes, "fg").map_or(WHITE, |fg| fg as u32);
+        match name {
+            "noise" => Some(Pattern::Noise {
+                seed: attr_int(attributes, "seed").unwrap_or(0) as u32,
+                cell: attr_int(attributes, "cell").unwrap_or(10).max(1) as f32,
+            }),
+            "checkerboard" => Some(Pattern::Checkerboard {
+                cell: attr_int(attributes, "cell").unwrap_or(8).max(1) as f32,
+                fg,
+            }),
+            "grid" => Some(Pattern::Grid {
+                spacing: attr_int(attributes, "spacing").unwrap_or(10).max(2) as f32,
+                fg,
+            }),
+            "gray_ramp" => Some(Pattern::GrayRamp),
+            "crosshair" => Some(Pattern::Crosshair { fg }),
+            "gradient" => Some(Pattern::Gradient {
+                gradient: Gradient {
+                    start: attr_int(attributes, "from").map_or(BLACK, |from| from as u32),
+                    end: attr_int(attributes, "to").map_or(WHITE, |to| to as u32),
+                    vertical: attr(attributes, "direction")
+                        .and_then(|direction| direction.as_text())
+                        == Some("vertical"),
+                    steps: attr_int(attributes, "steps").unwrap_or(0).clamp(0, 256) as u32,
+                },
+                labels: matches!(
+                    attr(attributes, "labels"),
+                    Some(OwnedAttributeValue::Bool(true))
+                ),
+            }),
+            "lines" => Some(Pattern::Lines {
+                every: attr_int(attributes, "every").unwrap_or(2).clamp(1, 256) as u32,
+                rows: matches!(
+                    attr(attributes, "rows"),
+                    Some(OwnedAttributeValue::Bool(true))
+                ),
+                fg,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn draw(&self, backend: &dyn Backend, x: f32, y: f32, z: f32, width: f32, height: f32) {
+        match *self {
+            Pattern::Noise { seed, cell } => {
+                let mut state = seed;
+                let mut cy = 0.0;
+                while cy < height {
+                    let mut cx = 0.0;
+                    while cx < width {
+                        state = xorshift32(state);
+                        backend.draw_rect(
+                            x + cx,
+                            y + cy,
+                            z,
+                            cell.min(width - cx),
+                            cell.min(height - cy),
+                            state | 0xff00_0000,
+                        );
+                        cx += cell;
+                    }
+                    cy += cell;
+                }
+            }
+            Pattern::Checkerboard { cell, fg } => {
+                let mut row = 0;
+                let mut cy = 0.0;
+                while cy < height {
+                    let mut cx = if row % 2 == 0 { 0.0 } else { cell };
+                    while cx < width {
+                        backend.draw_rect(
+                            x + cx,
+                            y + cy,
+                            z,
+                            cell.min(width - cx),
+                            cell.min(height - cy),
+                            fg,
+                        );
+                        cx += cell * 2.0;
+                    }
+                    row += 1;
+                    cy += cell;
+                }
+            }
+            Pattern::Grid { spacing, fg } => {
+                let mut cx = 0.0;
+                while cx < width {
+                    draw_vline(backend, x + cx, y, z, height, fg);
+                    cx += spacing;
+                }
+                draw_vline(backend, x + width - 1.0, y, z, height, fg);
+                let mut cy = 0.0;
+                while cy < height {
+                    draw_hline(backend, x, y + cy, z, width, fg);
+                    cy += spacing;
+                }
+                draw_hline(backend, x, y + height - 1.0, z, width, fg);
+            }
+            Pattern::GrayRamp => {
+                let band = width / 256.0;
+                for level in 0..=255_u8 {
+                    backend.draw_rect(
+                        x + band * level as f32,
+                        y,
+                        z,
+                        band,
+                        height,
+                        rgba(level, level, level, 0xff),
+                    );
+                }
+            }
+            Pattern::Crosshair { fg } => {
+                let cx = (width / 2.0).floor();
+                let cy = (height / 2.0).floor();
+                draw_vline(backend, x + cx, y, z, height, fg);
+                draw_hline(backend, x, y + cy, z, width, fg);
+                // mark the corner pixels
+                backend.draw_rect(x, y, z, 1.0, 1.0, fg);
+                backend.draw_rect(x + width - 1.0, y, z, 1.0, 1.0, fg);
+                backend.draw_rect(x, y + height - 1.0, z, 1.0, 1.0, fg);
+                backend.draw_rect(x + width - 1.0, y + height - 1.0, z, 1.0, 1.0, fg);
+            }
+            Pattern::Gradient { gradient, labels } => {
+                draw_gradient(backend, x, y, z, width, height, &gradient);
+                if labels {
+                    for (offset, size, color) in gradient.bands(width, height) {
+                        if gradient.vertical {
+                            draw_band_label(backend, x, y + offset, z, width, size, color);
+                        } else {
+                            draw_band_label(backend, x + offset, y, z, size, height, color);
+                        }
+                    }
+                }
+            }
+            // rects over whole pixels, lines could light the neighbours too
+            Pattern::Lines { every, rows, fg } => {
+                let (length, size) = if rows {
+                    (height, width)
+                } else {
+                    (width, height)
+                };
+                let mut offset = 0.0;
+                while offset < length {
+                    if rows {
+                        backend.draw_rect(x, y + offset, z, size, 1.0, fg);
+                    } else {
+                        backend.draw_rect(x + offset, y, z, 1.0, size, fg);
+                    }
+                    offset += every as f32;
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,16 @@
 use dioxus_native_core::{exports::shipyard::Component, node::OwnedAttributeValue, prelude::*};
 use dioxus_native_core_macro::partial_derive_state;
 
-use crate::{c2d::rgba, constant::MAX_DEEP_3D, utils::color_name_rgba};
+use crate::{color::rgba, constant::MAX_DEEP_3D, utils::color_name_rgba};
+
+// which image of the top screen a node is drawn into while 3d is on
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Eye {
+    #[default]
+    Both,
+    Left,
+    Right,
+}
 
 #[derive(Clone, Copy, PartialEq, Component)]
 pub struct RdomStyle {
@@ -11,8 +20,11 @@ pub struct RdomStyle {
     pub scale: f32,
     pub scale_placeholder: Option<f32>,
     pub deep_3d: f32,
+    pub eye: Eye,
     pub z_index: f32,
     pub max_width: Option<f32>,
+    // multiplied into the alpha of color and background_color, inherited
+    pub opacity: f32,
     screen: u8,
 }
 
@@ -32,8 +44,10 @@ impl Default for RdomStyle {
             scale_placeholder: None,
             screen: 0,
             deep_3d: 0.0,
+            eye: Eye::Both,
             max_width: None,
             z_index: 0.0,
+            opacity: 1.0,
         }
     }
 }
@@ -58,8 +72,10 @@ impl State for RdomStyle {
                 "scale",
                 "scale_placeholder",
                 "deep_3d",
+                "eye",
                 "z-index",
                 "max-width",
+                "opacity",
             ]));
 
     fn update<'a>(
@@ -72,16 +88,15 @@ impl State for RdomStyle {
     ) -> bool {
         // TextColor only depends on the color tag, so getting the first tag is equivalent to looking through all tags
         let mut new = RdomStyle::default();
-        match &parent {
-            Some((parent,)) => {
-                new.color = parent.color;
-                new.screen = parent.screen;
-                new.scale = parent.scale;
-                new.deep_3d = parent.deep_3d;
-                new.z_index = parent.z_index;
-                new.max_width = parent.max_width;
-            }
-            None => {}
+        if let Some((parent,)) = &parent {
+            new.color = parent.color;
+            new.screen = parent.screen;
+            new.scale = parent.scale;
+            new.deep_3d = parent.deep_3d;
+            new.eye = parent.eye;
+            new.z_index = parent.z_index;
+            new.max_width = parent.max_width;
+            new.opacity = parent.opacity;
         }
         if let Some(attributes) = node_view.attributes() {
             for attr in attributes {
@@ -133,10 +148,17 @@ impl State for RdomStyle {
                             },
                         };
 
-                        if new.deep_3d > MAX_DEEP_3D {
-                            new.deep_3d = MAX_DEEP_3D;
-                        } else if new.deep_3d < -MAX_DEEP_3D {
-                            new.deep_3d = -MAX_DEEP_3D;
+                        new.deep_3d = new.deep_3d.clamp(-MAX_DEEP_3D, MAX_DEEP_3D);
+                    }
+                    "eye" => {
+                        new.eye = match attr.value.as_text() {
+                            Some("left") => Eye::Left,
+                            Some("right") => Eye::Right,
+                            Some("both") => Eye::Both,
+                            _ => match &parent {
+                                Some((parent,)) => parent.eye,
+                                None => Eye::Both,
+                            },
                         }
                     }
                     "z-index" => {
@@ -159,6 +181,18 @@ impl State for RdomStyle {
                             },
                         }
                     }
+                    "opacity" => {
+                        let parent_opacity = match &parent {
+                            Some((parent,)) => parent.opacity,
+                            None => 1.0,
+                        };
+                        let opacity = match attr.value {
+                            OwnedAttributeValue::Float(opacity) => *opacity as f32,
+                            OwnedAttributeValue::Int(opacity) => *opacity as f32,
+                            _ => 1.0,
+                        };
+                        new.opacity = opacity.clamp(0.0, 1.0) * parent_opacity;
+                    }
                     "background-color" => {
                         new.background_color = match attr.value {
                             OwnedAttributeValue::Text(color) => Some(color_name_rgba(color)),
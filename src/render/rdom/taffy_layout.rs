@@ -7,7 +7,7 @@ use taffy::{
     Overflow, Point,
 };
 
-use crate::c2d::C2dText;
+use crate::render::backend::MeasureText;
 
 use super::rdom_style::RdomStyle;
 
@@ -259,13 +259,14 @@ impl State for TaffyLayout {
 
         {
             let taffy: &Arc<Mutex<TaffyTree<()>>> = ctx.get().unwrap();
+            let measure_text: &MeasureText = ctx.get().unwrap();
             let mut taffy = taffy.lock().expect("get taffy lock in rdom style");
             if let Some(text) = node_view.text() {
                 let (scale, max_width) = match parent {
                     Some((parent,)) => (parent.scale, parent.max_width),
                     _ => (1.0, None),
                 };
-                let (mut width, height) = C2dText::new(text).dimension(scale, scale);
+                let (mut width, height) = (measure_text.0)(text, scale);
                 if let Some(max_width) = max_width {
                     width = width.min(max_width);
                 }
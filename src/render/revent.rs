@@ -2,10 +2,8 @@ use std::{
     any::Any,
     rc::Rc,
     sync::{Arc, Mutex},
-    time::Instant,
 };
 
-use ctru::{os::current_3d_slider_state, services::hid::KeyPad};
 use dioxus::prelude::*;
 use dioxus_core::ElementId;
 use dioxus_elements::{
@@ -15,14 +13,42 @@ use dioxus_elements::{
 use dioxus_native_core::{prelude::*, tree::TreeRef};
 use taffy::TaffyTree;
 
-use crate::{
-    app::AppExit, render::rdom::taffy_layout::TaffyLayout, resource::Resource, utils::sleep_micros,
-};
+use crate::{platform::KeyPad, render::rdom::taffy_layout::TaffyLayout};
+
+#[cfg(target_os = "horizon")]
+use self::scan::ControllerState;
 
-use super::ImageDataSet;
+#[cfg(target_os = "horizon")]
+mod scan;
 
+// the code of a single button, Code::Unidentified for a combo or a release, every
+// button held and how they changed
 #[derive(Clone)]
-pub struct KeyEventData(pub Key, pub Code);
+pub struct KeyEventData(pub Key, pub Code, pub KeyPad, pub KeyChange);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum KeyChange {
+    // the buttons pressed since the last scan
+    Down(KeyPad),
+    // the buttons released since the last scan, nothing else was pressed
+    Up(KeyPad),
+    // the same buttons are still held, sent every 60 ms after 300 ms
+    Repeat,
+}
+
+impl KeyChange {
+    pub fn between(previous: KeyPad, current: KeyPad) -> Option<Self> {
+        let down = current - previous;
+        let up = previous - current;
+        if !down.is_empty() {
+            Some(KeyChange::Down(down))
+        } else if !up.is_empty() {
+            Some(KeyChange::Up(up))
+        } else {
+            None
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct MouseEventData(u16, u16);
@@ -188,7 +214,7 @@ impl HasKeyboardData for KeyEventData {
     }
 
     fn is_auto_repeating(&self) -> bool {
-        false
+        self.3 == KeyChange::Repeat
     }
 
     fn is_composing(&self) -> bool {
@@ -200,42 +226,59 @@ impl HasKeyboardData for KeyEventData {
     }
 }
 
-fn create_keyboard_event_data(keypad: KeyPad) -> Option<KeyEventData> {
-    match keypad {
-        KeyPad::A => Some((Key::Unidentified, Code::KeyA)),
-        KeyPad::B => Some((Key::Unidentified, Code::KeyB)),
-        KeyPad::Y => Some((Key::Unidentified, Code::KeyY)),
-        KeyPad::X => Some((Key::Unidentified, Code::KeyX)),
-        KeyPad::L => Some((Key::Unidentified, Code::KeyL)),
-        KeyPad::R => Some((Key::Unidentified, Code::KeyR)),
-        KeyPad::ZL => Some((Key::Unidentified, Code::ControlLeft)),
-        KeyPad::ZR => Some((Key::Unidentified, Code::ControlRight)),
-        KeyPad::DPAD_LEFT => Some((Key::Unidentified, Code::ArrowLeft)),
-        KeyPad::DPAD_RIGHT => Some((Key::Unidentified, Code::ArrowRight)),
-        KeyPad::DPAD_UP => Some((Key::Unidentified, Code::ArrowUp)),
-        KeyPad::DPAD_DOWN => Some((Key::Unidentified, Code::ArrowDown)),
-        KeyPad::START => Some((Key::Unidentified, Code::Enter)),
-        KeyPad::SELECT => Some((Key::Unidentified, Code::ShiftLeft)),
-        _ => None,
-    }
-    .map(|(key, code)| KeyEventData(key, code))
+// every button the app listens to, its name in the help and the settings, and its code
+pub const BUTTONS: [(KeyPad, &str, Code); 14] = [
+    (KeyPad::A, "A", Code::KeyA),
+    (KeyPad::B, "B", Code::KeyB),
+    (KeyPad::Y, "Y", Code::KeyY),
+    (KeyPad::X, "X", Code::KeyX),
+    (KeyPad::L, "L", Code::KeyL),
+    (KeyPad::R, "R", Code::KeyR),
+    (KeyPad::ZL, "ZL", Code::ControlLeft),
+    (KeyPad::ZR, "ZR", Code::ControlRight),
+    (KeyPad::DPAD_LEFT, "Left", Code::ArrowLeft),
+    (KeyPad::DPAD_RIGHT, "Right", Code::ArrowRight),
+    (KeyPad::DPAD_UP, "Up", Code::ArrowUp),
+    (KeyPad::DPAD_DOWN, "Down", Code::ArrowDown),
+    (KeyPad::START, "Start", Code::Enter),
+    (KeyPad::SELECT, "Select", Code::ShiftLeft),
+];
+
+fn known_buttons(keypad: KeyPad) -> KeyPad {
+    BUTTONS
+        .iter()
+        .map(|(button, ..)| *button)
+        .filter(|button| keypad.contains(*button))
+        .fold(KeyPad::empty(), |all, button| all | button)
+}
+
+fn create_keyboard_event_data(keypad: KeyPad, change: KeyChange) -> Option<KeyEventData> {
+    // touching the screen sets a bit too
+    let keypad = known_buttons(keypad);
+    let change = match change {
+        KeyChange::Down(down) => KeyChange::Down(known_buttons(down)),
+        KeyChange::Up(up) => KeyChange::Up(known_buttons(up)),
+        KeyChange::Repeat => KeyChange::Repeat,
+    };
+    let code = match change {
+        KeyChange::Down(down) if down.is_empty() => return None,
+        KeyChange::Repeat if keypad.is_empty() => return None,
+        // releasing a button is no press of the ones still held
+        KeyChange::Up(up) if up.is_empty() => return None,
+        KeyChange::Up(_) => Code::Unidentified,
+        _ => BUTTONS
+            .iter()
+            .find(|(button, ..)| *button == keypad)
+            .map_or(Code::Unidentified, |&(_, _, code)| code),
+    };
+    Some(KeyEventData(Key::Unidentified, code, keypad, change))
 }
 
 pub struct EventTrigger {
     pub(crate) mousedown_node_id: Option<ElementId>,
-    pub(crate) first_touch: (u16, u16),
-    pub(crate) touch: (u16, u16),
-    pub(crate) touch_at: Instant,
-    pub(crate) keypad: KeyPad,
-    pub(crate) last_keypad_at: Instant,
-    pub(crate) last_repeat_at: Instant,
-    pub(crate) input: Option<(
-        KeyPad,
-        f32,
-        Option<(u16, u16)>,
-        Option<(u16, u16)>,
-        Option<(u16, u16)>,
-    )>,
+    // the buttons and the touch from the last scan
+    #[cfg(target_os = "horizon")]
+    pub(crate) controller: ControllerState,
 }
 
 fn depth_first(rdom: &RealDom, mut f: impl FnMut(NodeRef<'_>) -> bool) {
@@ -260,7 +303,6 @@ fn get_parent_location(
     x: u16,
     y: u16,
     node: &NodeRef<'_>,
-    rdom: &RealDom,
     taffy: Arc<Mutex<TaffyTree<()>>>,
 ) -> (u16, u16) {
     let mut x = x;
@@ -276,7 +318,7 @@ fn get_parent_location(
         })
     };
     if res {
-        get_parent_location(x, y, &node.parent().unwrap(), rdom, taffy)
+        get_parent_location(x, y, &node.parent().unwrap(), taffy)
     } else {
         (x, y)
     }
@@ -286,19 +328,20 @@ impl EventTrigger {
     pub fn new() -> Self {
         Self {
             mousedown_node_id: None,
-            first_touch: (0, 0),
-            touch: (0, 0),
-            touch_at: Instant::now(),
-            keypad: KeyPad::empty(),
-            last_keypad_at: Instant::now(),
-            last_repeat_at: Instant::now(),
-            input: None,
+            #[cfg(target_os = "horizon")]
+            controller: ControllerState::new(),
         }
     }
 
-    fn handle_keyboard_event(&mut self, keypad: KeyPad, rdom: &RealDom, vdom: &mut VirtualDom) {
+    pub fn handle_keyboard_event(
+        &mut self,
+        keypad: KeyPad,
+        change: KeyChange,
+        rdom: &RealDom,
+        vdom: &mut VirtualDom,
+    ) {
         // event
-        if let Some(data) = create_keyboard_event_data(keypad) {
+        if let Some(data) = create_keyboard_event_data(keypad, change) {
             rdom.get_listening_sorted("keypress")
                 .into_iter()
                 .map(|node| node.mounted_id())
@@ -314,7 +357,7 @@ impl EventTrigger {
         }
     }
 
-    fn handle_mouse_event(
+    pub fn handle_mouse_event(
         &mut self,
         event_type: &str,
         click: (u16, u16),
@@ -343,7 +386,7 @@ impl EventTrigger {
             .into_iter()
             .filter(|node| node.mounted_id().is_some())
             .map(|node| {
-                let (px, py) = get_parent_location(0, 0, &node, rdom, taffy.clone());
+                let (px, py) = get_parent_location(0, 0, &node, taffy.clone());
                 let r = node.get::<TaffyLayout>().unwrap();
                 let lock = taffy.lock().expect("get taffy lock in mouse event");
                 let layout = lock.layout(r.node.unwrap()).unwrap();
@@ -382,130 +425,10 @@ impl EventTrigger {
                 .is_some()
         });
     }
+}
 
-    pub async fn scan_controller_input(&mut self, resource: &Rc<Resource>) {
-        self.input = None;
-        resource.hid.borrow_mut().scan_input();
-
-        // touch
-        let mut click = None;
-        let mut mousedown = None;
-        let mut mouseup = None;
-        let current_touch = resource.hid.borrow().touch_position();
-        if current_touch.0 == 0 && current_touch.1 == 0 {
-            if self.touch.0 != 0 || self.touch.1 != 0 {
-                mouseup = Some(self.touch);
-                if self.touch_at.elapsed().as_millis() < 1000
-                    && (self.first_touch.0 == 0 && self.first_touch.1 == 0
-                        || ((self.touch.0 as i32 - self.first_touch.0 as i32).pow(2)
-                            + (self.touch.1 as i32 - self.first_touch.1 as i32).pow(2))
-                            < 400)
-                {
-                    click = Some(self.touch);
-                }
-                self.touch = (0, 0);
-                self.first_touch = (0, 0);
-            }
-        } else {
-            if self.touch.0 == 0 && self.touch.1 == 0 {
-                self.touch_at = Instant::now();
-                self.first_touch = current_touch;
-                mousedown = Some(current_touch);
-            }
-            self.touch = current_touch;
-        }
-
-        // keypad
-        let mut keypad = resource.hid.borrow().keys_held();
-        if keypad != self.keypad {
-            self.keypad = keypad;
-            self.last_keypad_at = Instant::now();
-        } else if self.last_keypad_at.elapsed().as_millis() > 300
-            && self.last_repeat_at.elapsed().as_millis() > 60
-        {
-            self.last_repeat_at = Instant::now();
-        } else {
-            keypad = KeyPad::empty();
-        }
-        // 3d slider
-        let current_3d = current_3d_slider_state();
-
-        // cache input
-        self.input = Some((keypad, current_3d, click, mousedown, mouseup));
-
-        // wait for next frame
-        sleep_micros(0).await
-    }
-
-    pub async fn poll_event_and_wait_for_work(
-        &mut self,
-        resource: &Rc<Resource>,
-        rdom: &RealDom,
-        vdom: &mut VirtualDom,
-        current_3d: &f32,
-        current_new_3d: &mut f32,
-        images: &mut ImageDataSet,
-        taffy: Arc<Mutex<TaffyTree<()>>>,
-    ) {
-        while resource.main_loop() {
-            tokio::select! {
-                // wait for input
-                _ = self.scan_controller_input(resource) => {
-                    if let Some((keypad, new_3d, click, mousedown, mouseup)) = self.input.take() {
-                        // keyboard
-                        self.handle_keyboard_event(keypad, rdom, vdom);
-
-                        // click
-                        click.map(|point| {
-                            self.handle_mouse_event("click", point, rdom, vdom, taffy.clone());
-                        });
-
-                        // mouse down
-                        mousedown.map(|point| {
-                            self.handle_mouse_event("mousedown", point, rdom, vdom, taffy.clone());
-                        });
-
-                        mouseup.map(|point| {
-                            self.handle_mouse_event("mouseup", point, rdom, vdom, taffy.clone());
-                        });
-
-                        if new_3d != *current_3d {
-                            *current_new_3d = new_3d;
-                            break;
-                        }
-
-                        if images.is_update() {
-                            break;
-                        }
-
-                        if AppExit::is_exit() {
-                            break;
-                        }
-                    }
-                }
-                // wait for work
-                _ = vdom.wait_for_work() => {
-                    if let Some((keypad, _, click, mousedown, mouseup)) = self.input.take() {
-                        // keyboard
-                        self.handle_keyboard_event(keypad, rdom, vdom);
-
-                        // click
-                        click.map(|point| {
-                            self.handle_mouse_event("click", point, rdom, vdom, taffy.clone());
-                        });
-
-                        // mouse down
-                        mousedown.map(|point| {
-                            self.handle_mouse_event("mousedown", point, rdom, vdom, taffy.clone());
-                        });
-
-                        mouseup.map(|point| {
-                            self.handle_mouse_event("mouseup", point, rdom, vdom, taffy.clone());
-                        });
-                    }
-                    break;
-                }
-            }
-        }
+impl Default for EventTrigger {
+    fn default() -> Self {
+        Self::new()
     }
 }
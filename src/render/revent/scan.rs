@@ -0,0 +1,239 @@
+//! reads the buttons, the touch screen and the 3d slider of the console
+use std::{
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use dioxus::prelude::*;
+use dioxus_native_core::prelude::*;
+use taffy::TaffyTree;
+use tokio::time::Instant;
+
+use crate::{
+    app::{
+        input_lock::{AppLock, LockState, UNLOCK_HOLD},
+        App3d, AppExit,
+    },
+    platform::{current_3d_slider_state, KeyPad},
+    render::{image_data_set::ImageDataSet, Screens},
+    resource::Resource,
+    utils::sleep_micros,
+};
+
+use super::{EventTrigger, KeyChange};
+
+pub struct ControllerState {
+    first_touch: (u16, u16),
+    touch: (u16, u16),
+    touch_at: Instant,
+    keypad: KeyPad,
+    last_keypad_at: Instant,
+    last_repeat_at: Instant,
+    // set after unlocking until every button is released
+    wait_for_release: bool,
+    input: Option<(
+        Option<(KeyPad, KeyChange)>,
+        f32,
+        Option<(u16, u16)>,
+        Option<(u16, u16)>,
+        Option<(u16, u16)>,
+    )>,
+}
+
+impl ControllerState {
+    pub fn new() -> Self {
+        Self {
+            first_touch: (0, 0),
+            touch: (0, 0),
+            touch_at: Instant::now(),
+            keypad: KeyPad::empty(),
+            last_keypad_at: Instant::now(),
+            last_repeat_at: Instant::now(),
+            wait_for_release: false,
+            input: None,
+        }
+    }
+}
+
+impl EventTrigger {
+    pub async fn scan_controller_input(&mut self, resource: &Rc<Resource>) {
+        self.controller.input = None;
+        resource.hid.borrow_mut().scan_input();
+
+        // 3d slider
+        let current_3d = if App3d::is_enabled() {
+            current_3d_slider_state()
+        } else {
+            0.0
+        };
+
+        // locked, only the unlock combo is watched
+        if let Some(lock) = AppLock::state() {
+            self.scan_locked_input(resource, lock);
+            self.controller.input = Some((None, current_3d, None, None, None));
+            sleep_micros(0).await;
+            return;
+        }
+
+        // touch
+        let mut click = None;
+        let mut mousedown = None;
+        let mut mouseup = None;
+        let current_touch = resource.hid.borrow().touch_position();
+        if current_touch.0 == 0 && current_touch.1 == 0 {
+            if self.controller.touch.0 != 0 || self.controller.touch.1 != 0 {
+                mouseup = Some(self.controller.touch);
+                if self.controller.touch_at.elapsed().as_millis() < 1000
+                    && (self.controller.first_touch.0 == 0 && self.controller.first_touch.1 == 0
+                        || ((self.controller.touch.0 as i32
+                            - self.controller.first_touch.0 as i32)
+                            .pow(2)
+                            + (self.controller.touch.1 as i32
+                                - self.controller.first_touch.1 as i32)
+                                .pow(2))
+                            < 400)
+                {
+                    click = Some(self.controller.touch);
+                }
+                self.controller.touch = (0, 0);
+                self.controller.first_touch = (0, 0);
+            }
+        } else {
+            if self.controller.touch.0 == 0 && self.controller.touch.1 == 0 {
+                self.controller.touch_at = Instant::now();
+                self.controller.first_touch = current_touch;
+                mousedown = Some(current_touch);
+            }
+            self.controller.touch = current_touch;
+        }
+
+        // keypad
+        let keypad = resource.hid.borrow().keys_held();
+        let mut key = None;
+        // the unlock combo is still held, it should not start its action
+        if self.controller.wait_for_release {
+            self.controller.wait_for_release = !keypad.is_empty();
+            self.controller.keypad = keypad;
+        } else if let Some(change) = KeyChange::between(self.controller.keypad, keypad) {
+            self.controller.keypad = keypad;
+            self.controller.last_keypad_at = Instant::now();
+            key = Some((keypad, change));
+        } else if self.controller.last_keypad_at.elapsed().as_millis() > 300
+            && self.controller.last_repeat_at.elapsed().as_millis() > 60
+        {
+            self.controller.last_repeat_at = Instant::now();
+            key = Some((keypad, KeyChange::Repeat));
+        }
+
+        // cache input
+        self.controller.input = Some((key, current_3d, click, mousedown, mouseup));
+
+        // wait for next frame
+        sleep_micros(0).await
+    }
+
+    fn scan_locked_input(&mut self, resource: &Rc<Resource>, lock: LockState) {
+        let hid = resource.hid.borrow();
+        let keypad = hid.keys_held();
+        if keypad != self.controller.keypad {
+            self.controller.keypad = keypad;
+            self.controller.last_keypad_at = Instant::now();
+        }
+        // a touch started before the lock ends without a click
+        self.controller.touch = (0, 0);
+        self.controller.first_touch = (0, 0);
+        self.mousedown_node_id = None;
+
+        if !hid.keys_down().is_empty() || hid.touch_position() != (0, 0) {
+            AppLock::note_blocked();
+        }
+        // the combo has to be pressed again after locking, then held
+        if keypad == lock.combo
+            && self.controller.last_keypad_at > lock.locked_at
+            && self.controller.last_keypad_at.elapsed() >= UNLOCK_HOLD
+        {
+            AppLock::unlock();
+            self.controller.wait_for_release = true;
+        }
+    }
+
+    pub async fn poll_event_and_wait_for_work(
+        &mut self,
+        resource: &Rc<Resource>,
+        rdom: &RealDom,
+        vdom: &mut VirtualDom,
+        current_3d: &f32,
+        current_new_3d: &mut f32,
+        images: &mut ImageDataSet,
+        taffy: Arc<Mutex<TaffyTree<()>>>,
+    ) -> Screens {
+        while resource.main_loop() {
+            tokio::select! {
+                // wait for input
+                _ = self.scan_controller_input(resource) => {
+                    let input = self.controller.input.take();
+                    if let Some((key, new_3d, click, mousedown, mouseup)) = input {
+                        // keyboard
+                        if let Some((keypad, change)) = key {
+                            self.handle_keyboard_event(keypad, change, rdom, vdom);
+                        }
+
+                        // click
+                        click.map(|point| {
+                            self.handle_mouse_event("click", point, rdom, vdom, taffy.clone());
+                        });
+
+                        // mouse down
+                        mousedown.map(|point| {
+                            self.handle_mouse_event("mousedown", point, rdom, vdom, taffy.clone());
+                        });
+
+                        mouseup.map(|point| {
+                            self.handle_mouse_event("mouseup", point, rdom, vdom, taffy.clone());
+                        });
+
+                        if new_3d != *current_3d {
+                            *current_new_3d = new_3d;
+                            break;
+                        }
+
+                        // the images can be on both screens
+                        if images.is_update() {
+                            return Screens::ALL;
+                        }
+
+                        if AppExit::is_exit() {
+                            break;
+                        }
+                    }
+                }
+                // wait for work
+                _ = vdom.wait_for_work() => {
+                    let input = self.controller.input.take();
+                    if let Some((key, _, click, mousedown, mouseup)) = input {
+                        // keyboard
+                        if let Some((keypad, change)) = key {
+                            self.handle_keyboard_event(keypad, change, rdom, vdom);
+                        }
+
+                        // click
+                        click.map(|point| {
+                            self.handle_mouse_event("click", point, rdom, vdom, taffy.clone());
+                        });
+
+                        // mouse down
+                        mousedown.map(|point| {
+                            self.handle_mouse_event("mousedown", point, rdom, vdom, taffy.clone());
+                        });
+
+                        mouseup.map(|point| {
+                            self.handle_mouse_event("mouseup", point, rdom, vdom, taffy.clone());
+                        });
+                    }
+                    break;
+                }
+            }
+        }
+        Screens::default()
+    }
+}
@@ -0,0 +1,502 @@
+//! mounts a component like launch does, on the software backend, and compares
+//! the frames with the png files in `snapshots/`
+//!
+//! a missing golden file fails the test, `UPDATE_SNAPSHOTS=1` writes the
+//! golden files instead. a frame that differs is saved next to its golden
+//! file as `<name>.<screen>.new.png`.
+//!
+//! the tokio clock is paused, waiting moves it forward one frame at a time so
+//! timers and animations land on the same frames on every run.
+//!
+//! the settings and the history of Main go to a temp dir of each harness.
+use std::{
+    cell::Ref,
+    env,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use dioxus::prelude::*;
+use tokio::runtime::Runtime;
+
+use crate::{
+    app::{AppFrameDuration, AppPaths},
+    image_file::RgbaImage,
+    platform::KeyPad,
+    resource::Resource,
+};
+
+use super::{
+    backend::{Backend, MeasureText, Target},
+    frame::AnimationFrames,
+    revent::{EventTrigger, KeyChange},
+    software::{software_measure_text, Framebuffer, SoftwareBackend},
+    Document, Screens,
+};
+
+// a new 3ds frame
+const FRAME: Duration = Duration::from_micros(16_666);
+
+// harnesses mounted so far, names their temp dirs
+static MOUNTED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Step {
+    // the buttons held for one frame, then released
+    Keys(KeyPad),
+    // touch down on one frame and lift on the next
+    Tap(u16, u16),
+    // move the clock forward, timers and animation frames run on the way
+    Wait(Duration),
+}
+
+pub struct Harness {
+    runtime: Runtime,
+    document: Document,
+    backend: SoftwareBackend,
+    events: EventTrigger,
+    frames: AnimationFrames,
+    current_3d: f32,
+    // the files of AppPaths, removed with the harness
+    dir: PathBuf,
+}
+
+impl Harness {
+    pub fn mount(app: fn() -> Element) -> Self {
+        Self::mount_with(VirtualDom::new(app))
+    }
+
+    // for components that need a root context, the resource and the frames are
+    // provided like launch does, the frame duration like Main does and the paths
+    // in a temp dir
+    pub fn mount_with(vdom: VirtualDom) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .expect("build the snapshot runtime");
+        let resource = Resource::new(false).expect("the host resource");
+        let frames = AnimationFrames::default();
+        let dir = env::temp_dir().join(format!(
+            "cook_screen_snapshot_{}_{}",
+            process::id(),
+            MOUNTED.fetch_add(1, Ordering::Relaxed)
+        ));
+        let vdom = vdom
+            .with_root_context(resource)
+            .with_root_context(frames.clone())
+            .with_root_context(AppFrameDuration(FRAME.as_micros() as u64))
+            .with_root_context(AppPaths::in_dir(&dir.to_string_lossy()));
+        let document = {
+            let _guard = runtime.enter();
+            Document::new(vdom, MeasureText(software_measure_text))
+        };
+        let mut harness = Self {
+            runtime,
+            document,
+            backend: SoftwareBackend::new(),
+            events: EventTrigger::new(),
+            frames,
+            current_3d: 0.0,
+            dir,
+        };
+        harness.frame();
+        harness
+    }
+
+    // the 3d slider, the right eye is drawn too when it is not 0
+    pub fn set_3d(&mut self, current_3d: f32) {
+        self.current_3d = current_3d;
+        self.document.invalidate(Screens::TOP);
+        self.frame();
+    }
+
+    pub fn run(&mut self, steps: &[Step]) {
+        for step in steps {
+            match *step {
+                Step::Keys(keypad) => {
+                    for (held, change) in [
+                        (keypad, KeyChange::Down(keypad)),
+                        (KeyPad::empty(), KeyChange::Up(keypad)),
+                    ] {
+                        let Document { rdom, vdom, .. } = &mut self.document;
+                        self.events.handle_keyboard_event(held, change, rdom, vdom);
+                        self.frame();
+                    }
+                }
+                Step::Tap(x, y) => {
+                    self.touch("mousedown", (x, y));
+                    self.frame();
+                    self.touch("click", (x, y));
+                    self.touch("mouseup", (x, y));
+                    self.frame();
+                }
+                Step::Wait(length) => self.wait(length),
+            }
+        }
+    }
+
+    fn touch(&mut self, event_type: &str, point: (u16, u16)) {
+        let Document {
+            rdom, vdom, taffy, ..
+        } = &mut self.document;
+        self.events
+            .handle_mouse_event(event_type, point, rdom, vdom, taffy.clone());
+    }
+
+    // ticks the animation frames like launch does, a frame at a time
+    fn wait(&mut self, length: Duration) {
+        let mut waited = Duration::ZERO;
+        while waited < length {
+            let step = FRAME.min(length - waited);
+            self.runtime.block_on(tokio::time::advance(step));
+            waited += step;
+            if step == FRAME && self.frames.has_subscribers() {
+                self.frames.tick();
+            }
+            self.frame();
+        }
+    }
+
+    // apply the pending work and draw, layout and hit tests need it
+    pub fn frame(&mut self) -> Screens {
+        let vdom = &mut self.document.vdom;
+        let _ = self
+            .runtime
+            .block_on(async { tokio::time::timeout(Duration::ZERO, vdom.wait_for_work()).await });
+        let _guard = self.runtime.enter();
+        self.document.apply_mutations();
+        self.backend.set_3d(self.current_3d != 0.0);
+        self.document.draw(&self.backend, self.current_3d)
+    }
+
+    pub fn framebuffer(&self, target: Target) -> Ref<'_, Framebuffer> {
+        self.backend.framebuffer(target)
+    }
+
+    pub fn assert_snapshot(&self, name: &str) {
+        let mut failures = vec![];
+        for target in Target::ALL {
+            if target == Target::TopRight && !self.backend.is_3d() {
+                continue;
+            }
+            let framebuffer = self.framebuffer(target);
+            let path = golden_path(name, target, false);
+            let golden = fs::read(&path)
+                .ok()
+                .and_then(|data| RgbaImage::decode_png(&data).ok());
+            if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+                write_png(&path, &framebuffer);
+                continue;
+            }
+            match golden {
+                Some(golden) => {
+                    let different = count_different(&framebuffer, &golden);
+                    if different > 0 {
+                        write_png(&golden_path(name, target, true), &framebuffer);
+                        failures.push(format!("{}: {} pixels", path.display(), different));
+                    }
+                }
+                None => {
+                    write_png(&golden_path(name, target, true), &framebuffer);
+                    failures.push(format!("{}: missing", path.display()));
+                }
+            }
+        }
+        assert!(
+            failures.is_empty(),
+            "snapshots differ, {}, UPDATE_SNAPSHOTS=1 accepts them",
+            failures.join(", ")
+        );
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn screen_name(target: Target) -> &'static str {
+    match target {
+        Target::TopLeft => "top",
+        Target::TopRight => "top_right",
+        Target::Bottom => "bottom",
+    }
+}
+
+fn golden_path(name: &str, target: Target, is_new: bool) -> PathBuf {
+    let suffix = if is_new { ".new" } else { "" };
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(format!("{}.{}{}.png", name, screen_name(target), suffix))
+}
+
+// every pixel when the sizes differ
+fn count_different(framebuffer: &Framebuffer, golden: &RgbaImage) -> usize {
+    if golden.width != framebuffer.width || golden.height != framebuffer.height {
+        return (framebuffer.width * framebuffer.height) as usize;
+    }
+    framebuffer
+        .to_rgba8()
+        .chunks(4)
+        .zip(golden.pixels.chunks(4))
+        .filter(|(pixel, golden)| pixel != golden)
+        .count()
+}
+
+fn write_png(path: &Path, framebuffer: &Framebuffer) {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("create the snapshots dir");
+    }
+    let file = File::create(path).expect("create the snapshot file");
+    let mut encoder =
+        png::Encoder::new(BufWriter::new(file), framebuffer.width, framebuffer.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&framebuffer.to_rgba8()))
+        .expect("write the snapshot file");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{
+            color_cycle::{ColorCycle, ColorCycleFill},
+            cook_color::CookColor,
+            test_pattern::{TestPattern, TestPatternFill},
+            Main,
+        },
+        color::rgba,
+        constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT, SCREEN_TOP_WIDTH},
+    };
+
+    use super::*;
+
+    const BLUE: u32 = rgba(0x00, 0x00, 0xff, 0xff);
+    const BLACK: u32 = rgba(0x00, 0x00, 0x00, 0xff);
+    const RED: u32 = rgba(0xff, 0x00, 0x00, 0xff);
+    const GREEN: u32 = rgba(0x00, 0xff, 0x00, 0xff);
+    const WHITE: u32 = rgba(0xff, 0xff, 0xff, 0xff);
+
+    // a box moved by right and a button that turns white when tapped
+    #[allow(non_snake_case)]
+    fn Moving() -> Element {
+        let mut left = use_signal(|| 0);
+        let mut pressed = use_signal(|| false);
+        let button = if pressed() { WHITE } else { GREEN };
+
+        rsx! {
+            div {
+                "screen": "top",
+                "bg_reset": BLUE as i64,
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: SCREEN_TOP_WIDTH,
+                height: SCREEN_HEIGHT,
+            }
+            div {
+                "screen": "bottom",
+                "bg_reset": BLACK as i64,
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: SCREEN_BOTTOM_WIDTH,
+                height: SCREEN_HEIGHT,
+                onkeypress: move |event| {
+                    if event.code() == Code::ArrowRight {
+                        left += 10;
+                    }
+                },
+                div {
+                    position: "absolute",
+                    left: left(),
+                    top: 20,
+                    width: 40,
+                    height: 40,
+                    background_color: RED as i64,
+                }
+                div {
+                    position: "absolute",
+                    left: 200,
+                    top: 100,
+                    width: 60,
+                    height: 40,
+                    background_color: button as i64,
+                    onclick: move |_| pressed.set(true),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keys_and_taps_reach_the_component() {
+        let mut harness = Harness::mount(Moving);
+        assert_eq!(harness.framebuffer(Target::Bottom).pixel(0, 20), RED);
+        harness.run(&[
+            Step::Keys(KeyPad::DPAD_RIGHT),
+            Step::Keys(KeyPad::DPAD_RIGHT),
+            Step::Tap(220, 120),
+        ]);
+        let bottom = harness.framebuffer(Target::Bottom);
+        assert_eq!(bottom.pixel(0, 20), BLACK);
+        assert_eq!(bottom.pixel(20, 20), RED);
+        assert_eq!(bottom.pixel(200, 100), WHITE);
+        drop(bottom);
+        harness.assert_snapshot("moving");
+    }
+
+    // the box moves right on every frame
+    #[allow(non_snake_case)]
+    fn Animated() -> Element {
+        let mut left = use_signal(|| 0);
+        crate::render::frame::use_animation_frame(move |_| left += 1);
+
+        rsx! {
+            div {
+                "screen": "bottom",
+                "bg_reset": BLACK as i64,
+                position: "absolute",
+                left: 0,
+                top: 0,
+                width: SCREEN_BOTTOM_WIDTH,
+                height: SCREEN_HEIGHT,
+                div {
+                    position: "absolute",
+                    left: left(),
+                    top: 0,
+                    width: 1,
+                    height: 1,
+                    background_color: RED as i64,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn frames_tick_while_waiting() {
+        let mut harness = Harness::mount(Animated);
+        assert_eq!(harness.framebuffer(Target::Bottom).pixel(0, 0), RED);
+        harness.run(&[Step::Wait(Duration::from_millis(100))]);
+        // only the frames of this harness, whatever else runs
+        assert_eq!(harness.frames.count(), 6);
+        assert_eq!(harness.framebuffer(Target::Bottom).pixel(0, 0), BLACK);
+    }
+
+    // animates until a button is pressed
+    #[allow(non_snake_case)]
+    fn Stopping() -> Element {
+        let mut running = use_signal(|| true);
+
+        rsx! {
+            div {
+                "screen": "bottom",
+                onkeypress: move |_| running.set(false),
+                if running() {
+                    Animated {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_frames_tick_without_a_subscriber() {
+        let mut harness = Harness::mount(Stopping);
+        assert!(harness.frames.has_subscribers());
+        harness.run(&[Step::Keys(KeyPad::A)]);
+        assert!(!harness.frames.has_subscribers());
+        let count = harness.frames.count();
+        harness.run(&[Step::Wait(Duration::from_millis(100))]);
+        assert_eq!(harness.frames.count(), count);
+    }
+
+    #[test]
+    fn only_the_changed_screen_is_drawn() {
+        let mut harness = Harness::mount(Moving);
+        assert!(harness.frame().is_empty());
+        let Document { rdom, vdom, .. } = &mut harness.document;
+        harness.events.handle_keyboard_event(
+            KeyPad::DPAD_RIGHT,
+            KeyChange::Down(KeyPad::DPAD_RIGHT),
+            rdom,
+            vdom,
+        );
+        let bottom = Screens {
+            top: false,
+            bottom: true,
+        };
+        assert_eq!(harness.frame(), bottom);
+        harness.set_3d(1.0);
+        assert_eq!(harness.framebuffer(Target::TopRight).pixel(0, 0), BLUE);
+    }
+
+    #[test]
+    fn main_on_start() {
+        Harness::mount(Main).assert_snapshot("main");
+    }
+
+    #[test]
+    fn help_overlay() {
+        let mut harness = Harness::mount(Main);
+        harness.run(&[Step::Keys(KeyPad::SELECT)]);
+        harness.assert_snapshot("help_overlay");
+    }
+
+    #[test]
+    fn control_panel_slides_out() {
+        let mut harness = Harness::mount(Main);
+        harness.run(&[Step::Wait(Duration::from_secs(11))]);
+        harness.assert_snapshot("control_panel_hidden");
+    }
+
+    // the text scale of Main
+    const MAIN_SCALE: f64 = 0.4;
+
+    // red and blue a second each
+    #[allow(non_snake_case)]
+    fn Cycle() -> Element {
+        rsx! {
+            ColorCycleFill {
+                screen: "top",
+                cycle: ColorCycle {
+                    colors: vec![CookColor::Red, CookColor::Blue],
+                    interval_secs: 1,
+                    fade: false,
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn the_color_cycle_follows_the_paused_clock() {
+        let mut harness = Harness::mount(Cycle);
+        let top = |harness: &Harness| harness.framebuffer(Target::TopLeft).pixel(0, 0);
+        assert_eq!(top(&harness), CookColor::Red.rgba());
+        harness.run(&[Step::Wait(Duration::from_millis(900))]);
+        assert_eq!(top(&harness), CookColor::Red.rgba());
+        harness.run(&[Step::Wait(Duration::from_millis(200))]);
+        assert_eq!(top(&harness), CookColor::Blue.rgba());
+    }
+
+    #[allow(non_snake_case)]
+    fn Patterns() -> Element {
+        rsx! {
+            div { "scale": MAIN_SCALE,
+                TestPatternFill { screen: "top", pattern: TestPattern::Crosshair }
+                TestPatternFill { screen: "bottom", pattern: TestPattern::GraySteps }
+            }
+        }
+    }
+
+    #[test]
+    fn test_patterns() {
+        Harness::mount(Patterns).assert_snapshot("test_patterns");
+    }
+}
@@ -0,0 +1,419 @@
+//! draws into rgba framebuffers in memory, so the ui can run on the host
+//!
+//! the system font only exists on the console, text is drawn as one block
+//! per character with fixed metrics, which is enough to check the layout.
+use std::{
+    cell::{Cell, Ref, RefCell},
+    rc::Rc,
+};
+
+use crate::{
+    color::lerp_rgba,
+    constant::{SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT, SCREEN_TOP_WIDTH},
+    image_file::RgbaImage,
+};
+
+use super::backend::{Backend, C2dImageTrait, MeasureText, Target};
+
+// the system font at scale 1.0
+const LINE_HEIGHT: f32 = 30.0;
+const ASCII_ADVANCE: f32 = 13.0;
+const WIDE_ADVANCE: f32 = 24.0;
+
+fn advance(c: char, scale: f32) -> f32 {
+    if c.is_ascii() {
+        ASCII_ADVANCE * scale
+    } else {
+        WIDE_ADVANCE * scale
+    }
+}
+
+fn line_width(line: &str, scale: f32) -> f32 {
+    line.chars().map(|c| advance(c, scale)).sum()
+}
+
+// like C2D_TextGetDimensions, the size does not depend on the wrap width
+pub fn software_measure_text(text: &str, scale: f32) -> (f32, f32) {
+    let width = text
+        .lines()
+        .map(|line| line_width(line, scale))
+        .fold(0.0, f32::max);
+    let lines = text.lines().count().max(1);
+    (width, lines as f32 * LINE_HEIGHT * scale)
+}
+
+// break at spaces when a line gets wider than max_width, long words stay whole
+pub fn wrap_lines(text: &str, scale: f32, max_width: Option<f32>) -> Vec<String> {
+    let Some(max_width) = max_width else {
+        return text.lines().map(|line| line.to_string()).collect();
+    };
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let next = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && line_width(&next, scale) > max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = next;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn channel(color: u32, shift: u32) -> f32 {
+    ((color >> shift) & 0xff) as f32
+}
+
+// src over dst, the result is opaque like the screen
+fn blend(dst: u32, src: u32) -> u32 {
+    let alpha = channel(src, 24) / 255.0;
+    (0..3).fold(0xff00_0000, |color, idx| {
+        let shift = idx * 8;
+        let value = channel(src, shift) * alpha + channel(dst, shift) * (1.0 - alpha);
+        color | ((value.round() as u32) << shift)
+    })
+}
+
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    // rgba() colors row by row from the top
+    pixels: Vec<u32>,
+    depth: Vec<f32>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            pixels: vec![0xff00_0000; len],
+            depth: vec![0.0; len],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> u32 {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    // 4 bytes per pixel like RgbaImage
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| color.to_le_bytes())
+            .collect()
+    }
+
+    fn clear(&mut self, color: u32) {
+        self.pixels.fill(color | 0xff00_0000);
+        self.depth.fill(0.0);
+    }
+
+    // the depth test of citro2d, a higher or equal z is drawn over
+    fn plot(&mut self, x: i64, y: i64, z: f32, color: u32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let idx = y as usize * self.width as usize + x as usize;
+        if z < self.depth[idx] {
+            return;
+        }
+        self.depth[idx] = z;
+        self.pixels[idx] = blend(self.pixels[idx], color);
+    }
+
+    // the pixels whose centers are inside the rect
+    fn pixel_range(from: f32, size: f32) -> std::ops::Range<i64> {
+        (from - 0.5).ceil() as i64..(from + size - 0.5).ceil() as i64
+    }
+
+    fn fill(&mut self, x: f32, y: f32, z: f32, w: f32, h: f32, color: impl Fn(f32, f32) -> u32) {
+        for py in Self::pixel_range(y, h) {
+            for px in Self::pixel_range(x, w) {
+                let u = (px as f32 + 0.5 - x) / w;
+                let v = (py as f32 + 0.5 - y) / h;
+                self.plot(px, py, z, color(u, v));
+            }
+        }
+    }
+}
+
+// an sd card image, the software backend can not read c2d images back
+pub struct SoftwareImage(RgbaImage);
+
+impl C2dImageTrait for SoftwareImage {
+    fn get_pixels(&self) -> Option<&RgbaImage> {
+        Some(&self.0)
+    }
+}
+
+pub struct SoftwareBackend {
+    // in Target::ALL order
+    targets: RefCell<[Framebuffer; 3]>,
+    current: Cell<Target>,
+    is_3d: Cell<bool>,
+}
+
+impl Default for SoftwareBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoftwareBackend {
+    pub fn new() -> Self {
+        let (top, bottom, height) = (SCREEN_TOP_WIDTH, SCREEN_BOTTOM_WIDTH, SCREEN_HEIGHT);
+        Self {
+            targets: RefCell::new([
+                Framebuffer::new(top as u32, height as u32),
+                Framebuffer::new(top as u32, height as u32),
+                Framebuffer::new(bottom as u32, height as u32),
+            ]),
+            current: Cell::new(Target::TopLeft),
+            is_3d: Cell::new(false),
+        }
+    }
+
+    fn index(target: Target) -> usize {
+        Target::ALL.iter().position(|t| *t == target).unwrap_or(0)
+    }
+
+    pub fn framebuffer(&self, target: Target) -> Ref<'_, Framebuffer> {
+        Ref::map(self.targets.borrow(), |targets| {
+            &targets[Self::index(target)]
+        })
+    }
+
+    pub fn is_3d(&self) -> bool {
+        self.is_3d.get()
+    }
+
+    fn with_current(&self, f: impl FnOnce(&mut Framebuffer)) {
+        f(&mut self.targets.borrow_mut()[Self::index(self.current.get())]);
+    }
+}
+
+impl Backend for SoftwareBackend {
+    fn start_drawing(&self) {}
+
+    fn end_drawing(&self) {}
+
+    fn start_scene(&self, target: Target) {
+        self.current.set(target);
+    }
+
+    fn clear_scene(&self, target: Target, color: u32) {
+        self.targets.borrow_mut()[Self::index(target)].clear(color);
+    }
+
+    fn set_3d(&self, enabled: bool) {
+        self.is_3d.set(enabled);
+    }
+
+    fn draw_rect(&self, x: f32, y: f32, z: f32, w: f32, h: f32, color: u32) {
+        self.with_current(|fb| fb.fill(x, y, z, w, h, |_, _| color));
+    }
+
+    fn draw_rect_gradient(&self, x: f32, y: f32, z: f32, w: f32, h: f32, colors: [u32; 4]) {
+        self.with_current(|fb| {
+            fb.fill(x, y, z, w, h, |u, v| {
+                let top = lerp_rgba(colors[0], colors[1], u);
+                let bottom = lerp_rgba(colors[2], colors[3], u);
+                lerp_rgba(top, bottom, v)
+            })
+        });
+    }
+
+    fn draw_line(
+        &self,
+        x0: f32,
+        y0: f32,
+        color0: u32,
+        x1: f32,
+        y1: f32,
+        color1: u32,
+        width: f32,
+        z: f32,
+    ) {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length2 = (dx * dx + dy * dy).max(f32::EPSILON);
+        let half = width / 2.0;
+        let (left, top) = (x0.min(x1) - half, y0.min(y1) - half);
+        let (w, h) = ((x1 - x0).abs() + width, (y1 - y0).abs() + width);
+        self.with_current(|fb| {
+            for py in Framebuffer::pixel_range(top, h) {
+                for px in Framebuffer::pixel_range(left, w) {
+                    let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+                    let t = (((cx - x0) * dx + (cy - y0) * dy) / length2).clamp(0.0, 1.0);
+                    let (nx, ny) = (x0 + dx * t, y0 + dy * t);
+                    if (cx - nx).powi(2) + (cy - ny).powi(2) < half * half {
+                        fb.plot(px, py, z, lerp_rgba(color0, color1, t));
+                    }
+                }
+            }
+        });
+    }
+
+    fn draw_text(
+        &self,
+        text: &str,
+        x: f32,
+        y: f32,
+        z: f32,
+        scale: f32,
+        color: u32,
+        max_width: Option<f32>,
+    ) {
+        let line_height = LINE_HEIGHT * scale;
+        self.with_current(|fb| {
+            for (row, line) in wrap_lines(text, scale, max_width).iter().enumerate() {
+                let top = y + row as f32 * line_height;
+                let mut left = x;
+                for c in line.chars() {
+                    let width = advance(c, scale);
+                    if !c.is_whitespace() {
+                        // the body of the glyph, without the bearings and the descender
+                        fb.fill(
+                            left + width * 0.1,
+                            top + line_height * 0.2,
+                            z,
+                            width * 0.8,
+                            line_height * 0.6,
+                            |_, _| color,
+                        );
+                    }
+                    left += width;
+                }
+            }
+        });
+    }
+
+    fn measure_text(&self) -> MeasureText {
+        MeasureText(software_measure_text)
+    }
+
+    fn draw_image(&self, image: &dyn C2dImageTrait, x: f32, y: f32, z: f32, scale: f32) {
+        let Some(image) = image.get_pixels() else {
+            return;
+        };
+        let (w, h) = (image.width as f32 * scale, image.height as f32 * scale);
+        self.with_current(|fb| {
+            fb.fill(x, y, z, w, h, |u, v| {
+                let sx = ((u * image.width as f32) as u32).min(image.width - 1);
+                let sy = ((v * image.height as f32) as u32).min(image.height - 1);
+                let idx = (sy * image.width + sx) as usize * 4;
+                let p = &image.pixels[idx..idx + 4];
+                u32::from_le_bytes([p[0], p[1], p[2], p[3]])
+            })
+        });
+    }
+
+    fn sheet_image(&self, _idx: usize) -> Option<Rc<dyn C2dImageTrait + '_>> {
+        None
+    }
+
+    fn load_rgba_image(&self, image: RgbaImage) -> Option<Rc<dyn C2dImageTrait>> {
+        Some(Rc::new(SoftwareImage(image)))
+    }
+
+    // the title icons come from the console
+    fn load_icon(&self, _buffer: &[u16]) -> Option<Rc<dyn C2dImageTrait>> {
+        None
+    }
+
+    fn load_qrcode(&self, buffer: &[u8]) -> Option<Rc<dyn C2dImageTrait>> {
+        let size = (buffer.len() as f64).sqrt() as u32;
+        if size == 0 || (size * size) as usize != buffer.len() {
+            return None;
+        }
+        let pixels = buffer
+            .iter()
+            .flat_map(|&luma| [luma, luma, luma, 0xff])
+            .collect();
+        self.load_rgba_image(RgbaImage {
+            width: size,
+            height: size,
+            pixels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::rgba;
+
+    const RED: u32 = rgba(0xff, 0x00, 0x00, 0xff);
+    const BLUE: u32 = rgba(0x00, 0x00, 0xff, 0xff);
+
+    #[test]
+    fn rects_cover_whole_pixels() {
+        let backend = SoftwareBackend::new();
+        backend.start_scene(Target::Bottom);
+        backend.clear_scene(Target::Bottom, rgba(0, 0, 0, 0xff));
+        backend.draw_rect(2.0, 3.0, 0.0, 1.0, 2.0, RED);
+        let fb = backend.framebuffer(Target::Bottom);
+        assert_eq!(fb.pixel(2, 3), RED);
+        assert_eq!(fb.pixel(2, 4), RED);
+        assert_ne!(fb.pixel(1, 3), RED);
+        assert_ne!(fb.pixel(3, 3), RED);
+        assert_ne!(fb.pixel(2, 5), RED);
+    }
+
+    #[test]
+    fn lower_z_stays_behind() {
+        let backend = SoftwareBackend::new();
+        backend.start_scene(Target::TopLeft);
+        backend.draw_rect(0.0, 0.0, 1.0, 4.0, 4.0, RED);
+        backend.draw_rect(0.0, 0.0, 0.0, 4.0, 4.0, BLUE);
+        assert_eq!(backend.framebuffer(Target::TopLeft).pixel(1, 1), RED);
+        backend.draw_rect(0.0, 0.0, 1.0, 4.0, 4.0, BLUE);
+        assert_eq!(backend.framebuffer(Target::TopLeft).pixel(1, 1), BLUE);
+    }
+
+    #[test]
+    fn alpha_is_blended() {
+        let backend = SoftwareBackend::new();
+        backend.start_scene(Target::Bottom);
+        backend.clear_scene(Target::Bottom, BLUE);
+        backend.draw_rect(0.0, 0.0, 0.0, 1.0, 1.0, rgba(0xff, 0x00, 0x00, 0x80));
+        let [r, g, b, a] = backend
+            .framebuffer(Target::Bottom)
+            .pixel(0, 0)
+            .to_le_bytes();
+        assert_eq!((r, g, a), (0x80, 0x00, 0xff));
+        assert_eq!(b, 0x7f);
+    }
+
+    #[test]
+    fn one_pixel_lines() {
+        let backend = SoftwareBackend::new();
+        backend.start_scene(Target::Bottom);
+        backend.draw_line(5.5, 0.0, RED, 5.5, 10.0, RED, 1.0, 0.0);
+        let fb = backend.framebuffer(Target::Bottom);
+        assert_eq!(fb.pixel(5, 0), RED);
+        assert_eq!(fb.pixel(5, 9), RED);
+        assert_ne!(fb.pixel(4, 5), RED);
+        assert_ne!(fb.pixel(6, 5), RED);
+        assert_ne!(fb.pixel(5, 10), RED);
+    }
+
+    #[test]
+    fn text_wraps_at_spaces() {
+        let width = line_width("ab", 1.0);
+        assert_eq!(wrap_lines("ab cd ef", 1.0, Some(width)), ["ab", "cd", "ef"]);
+        assert_eq!(wrap_lines("ab cd", 1.0, None), ["ab cd"]);
+        assert_eq!(
+            software_measure_text("ab\n中", 0.5),
+            (ASCII_ADVANCE, LINE_HEIGHT)
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! eased transitions of numbers and colors, stepped on the animation frames
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use dioxus::prelude::*;
+use tokio::time::Instant;
+
+use crate::{color::lerp_rgba, utils::ease_out_expo};
+
+use super::frame::AnimationFrames;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Easing {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    OutCubic,
+    InOutCubic,
+    OutExpo,
+}
+
+impl Easing {
+    // how far from the start to the end, 0.0 to 1.0
+    pub fn progress(&self, elapsed: Duration, length: Duration) -> f64 {
+        if elapsed >= length {
+            return 1.0;
+        }
+        let t = elapsed.as_secs_f64() / length.as_secs_f64();
+        match self {
+            Easing::Linear => t,
+            Easing::InQuad => t * t,
+            Easing::OutQuad => 1.0 - (1.0 - t).powi(2),
+            Easing::InOutQuad if t < 0.5 => 2.0 * t * t,
+            Easing::InOutQuad => 1.0 - (2.0 - 2.0 * t).powi(2) / 2.0,
+            Easing::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::InOutCubic if t < 0.5 => 4.0 * t * t * t,
+            Easing::InOutCubic => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            Easing::OutExpo => ease_out_expo(elapsed, length, 0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub started_at: Instant,
+    pub length: Duration,
+    pub easing: Easing,
+}
+
+impl<T: Copy> Tween<T> {
+    pub fn value_at(&self, now: Instant, mix: fn(T, T, f64) -> T) -> T {
+        let progress = self.easing.progress(now - self.started_at, self.length);
+        mix(self.from, self.to, progress)
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        now - self.started_at >= self.length
+    }
+}
+
+fn mix_number(from: f64, to: f64, progress: f64) -> f64 {
+    from + (to - from) * progress
+}
+
+fn mix_color(from: u32, to: u32, progress: f64) -> u32 {
+    lerp_rgba(from, to, progress as f32)
+}
+
+// follows `target` from where the value is when it changes, frames are only asked
+// for while a transition runs
+fn use_transition<T: Copy + PartialEq + 'static>(
+    target: T,
+    length: Duration,
+    easing: Easing,
+    mix: fn(T, T, f64) -> T,
+) -> ReadOnlySignal<T> {
+    let frames = use_context::<AnimationFrames>();
+    let mut value = use_signal(|| target);
+    let state = use_hook(|| Rc::new(Cell::new((target, None::<Task>))));
+
+    let (last_target, last_task) = state.get();
+    if target != last_target {
+        if let Some(task) = last_task {
+            task.cancel();
+        }
+        let tween = Tween {
+            from: *value.peek(),
+            to: target,
+            started_at: Instant::now(),
+            length,
+            easing,
+        };
+        let task = spawn(async move {
+            let _subscription = frames.subscribe();
+            loop {
+                frames.next().await;
+                let now = Instant::now();
+                value.set(tween.value_at(now, mix));
+                if tween.is_done(now) {
+                    break;
+                }
+            }
+        });
+        state.set((target, Some(task)));
+    }
+
+    value.into()
+}
+
+// left, top or opacity
+pub fn use_tween(target: f64, length: Duration, easing: Easing) -> ReadOnlySignal<f64> {
+    use_transition(target, length, easing, mix_number)
+}
+
+// an rgba() color, every channel on the same curve
+pub fn use_color_tween(target: u32, length: Duration, easing: Easing) -> ReadOnlySignal<u32> {
+    use_transition(target, length, easing, mix_color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASINGS: [Easing; 7] = [
+        Easing::Linear,
+        Easing::InQuad,
+        Easing::OutQuad,
+        Easing::InOutQuad,
+        Easing::OutCubic,
+        Easing::InOutCubic,
+        Easing::OutExpo,
+    ];
+
+    #[test]
+    fn curves_start_at_0_and_end_at_1() {
+        let length = Duration::from_millis(400);
+        for easing in EASINGS {
+            assert_eq!(easing.progress(Duration::ZERO, length), 0.0, "{:?}", easing);
+            assert_eq!(easing.progress(length, length), 1.0, "{:?}", easing);
+            let half = easing.progress(length / 2, length);
+            assert!((0.0..=1.0).contains(&half), "{:?}", easing);
+        }
+        let half = Easing::InOutQuad.progress(length / 2, length);
+        assert!((half - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn colors_are_mixed_per_channel() {
+        let now = Instant::now();
+        let tween = Tween {
+            from: 0xff00_0000,
+            to: 0xff00_00ff,
+            started_at: now,
+            length: Duration::from_millis(100),
+            easing: Easing::Linear,
+        };
+        assert_eq!(tween.value_at(now, mix_color), 0xff00_0000);
+        let half = tween.value_at(now + Duration::from_millis(50), mix_color);
+        assert_eq!(half, 0xff00_0080);
+        assert!(tween.is_done(now + Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    cell::{Cell, RefCell},
+    error::Error,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use ctru::{prelude::*, services::am::Am};
+use log::info;
+
+use crate::{
+    c2d::C2D,
+    platform::{
+        enable_hight_performance_for_new_3ds, is_new_3ds, is_shell_open, setup_log_redirect,
+        watch_home_menu, Backlight, SleepGuard,
+    },
+    utils::sleep_micros,
+};
+
+// how often the lid and the backlights are checked
+const POWER_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct Resource {
+    pub soc: Soc,
+    pub hid: RefCell<Hid>,
+    pub c2d: Rc<C2D>,
+    // dropped before apt
+    _sleep: SleepGuard,
+    pub apt: Apt,
+    pub backlight: Backlight,
+    shell_open: Cell<bool>,
+    power_checked_at: Cell<Instant>,
+    _am: Am,
+}
+
+impl Resource {
+    pub fn new(enable_log_redirect: bool) -> Result<Rc<Self>, Box<dyn Error>> {
+        // enable high performance for new 3ds
+        if is_new_3ds() {
+            enable_hight_performance_for_new_3ds();
+        }
+        // enable socket for network
+        let mut soc = Soc::new()?;
+        // set log redirect
+        if enable_log_redirect {
+            setup_log_redirect(&mut soc);
+        }
+        // am init
+        let _am = Am::new()?;
+        // applet init
+        let apt = Apt::new()?;
+        // to tell a close from the home menu apart from the power button
+        watch_home_menu();
+        // no sleep or power saving while cooking
+        let _sleep = SleepGuard::new();
+        // hid init
+        let hid = Hid::new()?;
+        // remember the brightness to restore it on exit
+        let backlight = Backlight::new();
+        // c2d init
+        let c2d = Rc::new(C2D::new()?);
+
+        Ok(Rc::new(Self {
+            soc,
+            hid: RefCell::new(hid),
+            c2d,
+            _sleep,
+            apt,
+            backlight,
+            shell_open: Cell::new(is_shell_open().unwrap_or(true)),
+            power_checked_at: Cell::new(Instant::now()),
+            _am,
+        }))
+    }
+
+    pub fn main_loop(&self) -> bool {
+        self.apt.main_loop()
+    }
+
+    // resolves when the lid is opened again, the screens need a redraw then
+    pub async fn wait_for_shell_reopened(&self) {
+        loop {
+            // the loop in launch restarts this often, keep the interval across calls
+            let elapsed = self.power_checked_at.get().elapsed();
+            if elapsed < POWER_CHECK_INTERVAL {
+                sleep_micros((POWER_CHECK_INTERVAL - elapsed).as_micros() as u64).await;
+            }
+            self.power_checked_at.set(Instant::now());
+            // the system dims the backlights after a while without input
+            self.backlight.keep_pinned();
+            let Some(open) = is_shell_open() else {
+                continue;
+            };
+            if self.shell_open.replace(open) == open {
+                continue;
+            }
+            if open {
+                info!("lid opened");
+                self.backlight.power_on();
+                return;
+            }
+            info!("lid closed, keep cooking");
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::{error::Error, rc::Rc};
+
+use crate::platform::Backlight;
+
+// what the components take from the console, the host has nothing to set up
+pub struct Resource {
+    pub backlight: Backlight,
+}
+
+impl Resource {
+    pub fn new(_enable_log_redirect: bool) -> Result<Rc<Self>, Box<dyn Error>> {
+        Ok(Rc::new(Self {
+            backlight: Backlight::new(),
+        }))
+    }
+}
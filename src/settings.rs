@@ -0,0 +1,440 @@
+//! settings file, one `key=value` per line
+//!
+//! ```text
+//! version=3
+//! top.mode=color_cycle
+//! top.cycle_colors=white,red,#ff8000ff
+//! bottom.mode=solid
+//! bottom.solid_color=sunset
+//! preset.sunset=#ff8000ff
+//! key.exit=L+R+Start
+//! ```
+//!
+//! unknown keys are ignored and invalid values fall back to the defaults,
+//! so a broken file never stops the app from starting. two actions sharing
+//! a combo reset every key to its default.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use log::warn;
+
+use crate::{
+    app::{
+        cook_color::{is_valid_preset_name, CookColor},
+        cook_mode::CookMode,
+        key_binding::{combo_text, parse_combo, Action, KeyBindings},
+        screen_config::ScreenConfig,
+        session_timer::SessionTimer,
+        stereo_3d::Stereo3dPattern,
+        test_pattern::TestPattern,
+    },
+    platform::MAX_BACKLIGHT_LEVEL,
+};
+
+pub const SETTINGS_VERSION: u32 = 3;
+
+// version 1 had one mode for both screens
+const SCREEN_KEYS_V1: [&str; 4] = ["mode", "solid_color", "cycle_colors", "cycle_interval"];
+
+// longest cycle interval or session, one day
+const MAX_SECS: u64 = 24 * 3600;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Settings {
+    pub top: ScreenConfig,
+    pub bottom: ScreenConfig,
+    pub timer: SessionTimer,
+    pub enable_3d: bool,
+    // named colors from the color picker
+    pub presets: BTreeMap<String, u32>,
+    // button combos of the main screen actions
+    pub keys: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            top: ScreenConfig::default(),
+            bottom: ScreenConfig::default(),
+            timer: SessionTimer::default(),
+            enable_3d: true,
+            presets: BTreeMap::new(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+pub(crate) fn parse_bool(text: &str) -> Option<bool> {
+    match text {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_secs(text: &str) -> Option<u64> {
+    text.parse::<u64>().ok().filter(|secs| *secs <= MAX_SECS)
+}
+
+fn parse_colors(text: &str, presets: &BTreeMap<String, u32>) -> Option<Vec<CookColor>> {
+    text.split(',')
+        .map(|color| color.trim())
+        .filter(|color| !color.is_empty())
+        .map(|color| CookColor::parse_with(color, presets))
+        .collect::<Option<Vec<_>>>()
+        .filter(|colors| !colors.is_empty())
+}
+
+// bring the values of an older file up to SETTINGS_VERSION
+fn migrate(version: u32, values: &mut HashMap<String, String>) {
+    if version > SETTINGS_VERSION {
+        warn!(
+            "settings version {} is newer than {}, only known keys are loaded",
+            version, SETTINGS_VERSION
+        );
+    }
+    // files without a version line are read as version 1
+    if version < 2 {
+        // the shared mode is used for both screens
+        for key in SCREEN_KEYS_V1 {
+            if let Some(value) = values.remove(key) {
+                values.insert(format!("top.{}", key), value.clone());
+                values.insert(format!("bottom.{}", key), value);
+            }
+        }
+    }
+    if version < 3 {
+        // version 2 had one brightness for both backlights
+        if let Some(value) = values.remove("brightness") {
+            values.insert("top.brightness".to_string(), value.clone());
+            values.insert("bottom.brightness".to_string(), value);
+        }
+    }
+}
+
+// colors can be given by a preset name
+pub(crate) fn parse_screen_value(
+    config: &mut ScreenConfig,
+    key: &str,
+    value: &str,
+    presets: &BTreeMap<String, u32>,
+) -> Option<()> {
+    match key {
+        "mode" => CookMode::parse(value).map(|mode| config.mode = mode),
+        "solid_color" => {
+            CookColor::parse_with(value, presets).map(|color| config.solid_color = color)
+        }
+        "cycle_colors" => parse_colors(value, presets).map(|colors| config.cycle.colors = colors),
+        "cycle_interval" => parse_secs(value)
+            .filter(|secs| *secs > 0)
+            .map(|secs| config.cycle.interval_secs = secs),
+        "cycle_fade" => parse_bool(value).map(|fade| config.cycle.fade = fade),
+        "pattern" => TestPattern::parse(value).map(|pattern| config.pattern = pattern),
+        "stereo_pattern" => Stereo3dPattern::parse(value).map(|stereo| config.stereo = stereo),
+        // a file name in the images folder, empty for none
+        "burn_in_image" => Some(value).filter(|name| !name.contains('/')).map(|name| {
+            config.burn_in.image = Some(name.to_string()).filter(|name| !name.is_empty())
+        }),
+        "burn_in_length" => parse_secs(value)
+            .filter(|secs| *secs > 0)
+            .map(|secs| config.burn_in.length_secs = secs),
+        "brightness" => value
+            .parse::<u8>()
+            .ok()
+            .filter(|level| *level <= MAX_BACKLIGHT_LEVEL)
+            .map(|level| config.brightness = level),
+        _ => Some(()),
+    }
+}
+
+// colors with a preset are written as its name
+pub(crate) fn screen_lines(
+    screen: &str,
+    config: &ScreenConfig,
+    presets: &BTreeMap<String, u32>,
+) -> Vec<String> {
+    let colors = config
+        .cycle
+        .colors
+        .iter()
+        .map(|color| color.to_text_with(presets))
+        .collect::<Vec<_>>()
+        .join(",");
+    vec![
+        format!("{}.mode={}", screen, config.mode.name()),
+        format!(
+            "{}.solid_color={}",
+            screen,
+            config.solid_color.to_text_with(presets)
+        ),
+        format!("{}.cycle_colors={}", screen, colors),
+        format!("{}.cycle_interval={}", screen, config.cycle.interval_secs),
+        format!("{}.cycle_fade={}", screen, config.cycle.fade),
+        format!("{}.pattern={}", screen, config.pattern.name()),
+        format!("{}.stereo_pattern={}", screen, config.stereo.name()),
+        format!(
+            "{}.burn_in_image={}",
+            screen,
+            config.burn_in.image.as_deref().unwrap_or("")
+        ),
+        format!("{}.burn_in_length={}", screen, config.burn_in.length_secs),
+        format!("{}.brightness={}", screen, config.brightness),
+    ]
+}
+
+impl Settings {
+    pub fn parse(text: &str) -> Self {
+        let mut values = HashMap::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                values.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        let version = values
+            .get("version")
+            .and_then(|version| version.parse::<u32>().ok())
+            .unwrap_or(0);
+        migrate(version, &mut values);
+
+        // the presets first, colors can use their names
+        let mut values = values.into_iter().collect::<Vec<_>>();
+        values.sort_by_key(|(key, _)| !key.starts_with("preset."));
+
+        let mut settings = Self::default();
+        for (key, value) in values.iter() {
+            let valid = match key.as_str() {
+                "version" => Some(()),
+                "timer_length" => parse_secs(value).map(|secs| settings.timer.length_secs = secs),
+                "show_countdown" => {
+                    parse_bool(value).map(|show| settings.timer.show_countdown = show)
+                }
+                "enable_3d" => parse_bool(value).map(|enable| settings.enable_3d = enable),
+                _ => match key.split_once('.') {
+                    Some(("top", key)) => {
+                        parse_screen_value(&mut settings.top, key, value, &settings.presets)
+                    }
+                    Some(("bottom", key)) => {
+                        parse_screen_value(&mut settings.bottom, key, value, &settings.presets)
+                    }
+                    Some(("preset", name)) => CookColor::parse_with(value, &BTreeMap::new())
+                        .filter(|_| is_valid_preset_name(name))
+                        .map(|color| {
+                            settings.presets.insert(name.to_string(), color.rgba());
+                        }),
+                    Some(("key", name)) => Action::parse(name)
+                        .zip(parse_combo(value))
+                        .map(|(action, combo)| settings.keys.set(action, combo)),
+                    _ => Some(()),
+                },
+            }
+            .is_some();
+            if !valid {
+                warn!("invalid settings value {}={}", key, value);
+            }
+        }
+        // one combo can only start one action
+        if let Some((first, second)) = settings.keys.duplicate() {
+            warn!(
+                "key.{} and key.{} share a combo, the default keys are used",
+                first.name(),
+                second.name()
+            );
+            settings.keys = KeyBindings::default();
+        }
+
+        settings
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("version={}", SETTINGS_VERSION)];
+        lines.extend(screen_lines("top", &self.top, &self.presets));
+        lines.extend(screen_lines("bottom", &self.bottom, &self.presets));
+        lines.extend([
+            format!("timer_length={}", self.timer.length_secs),
+            format!("show_countdown={}", self.timer.show_countdown),
+            format!("enable_3d={}", self.enable_3d),
+        ]);
+        lines.extend(self.presets.iter().map(|(name, color)| {
+            format!("preset.{}={}", name, CookColor::Custom(*color).to_text())
+        }));
+        lines.extend(Action::ALL.iter().map(|action| {
+            format!(
+                "key.{}={}",
+                action.name(),
+                combo_text(self.keys.combo(*action))
+            )
+        }));
+        lines.join("\n") + "\n"
+    }
+
+    // a missing or unreadable file gives the defaults
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    warn!("failed to read settings: {}", err);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{burn_in::BurnIn, color_cycle::ColorCycle, cook_color::COOK_COLORS},
+        color::rgba,
+        platform::KeyPad,
+    };
+
+    #[test]
+    fn empty_file_gives_defaults() {
+        assert_eq!(Settings::parse(""), Settings::default());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut keys = KeyBindings::default();
+        keys.set(Action::Exit, KeyPad::L | KeyPad::R | KeyPad::START);
+        let settings = Settings {
+            top: ScreenConfig {
+                mode: CookMode::ColorCycle,
+                stereo: Stereo3dPattern::Crosstalk,
+                cycle: ColorCycle {
+                    colors: vec![CookColor::Red, CookColor::custom(0xff, 0x80, 0x00, 0xff)],
+                    interval_secs: 30,
+                    fade: true,
+                },
+                ..Default::default()
+            },
+            bottom: ScreenConfig {
+                mode: CookMode::TestPattern,
+                solid_color: CookColor::custom(0x12, 0x34, 0x56, 0xff),
+                pattern: TestPattern::Grid,
+                burn_in: BurnIn {
+                    image: Some("home menu.png".to_string()),
+                    length_secs: 600,
+                    preview: false,
+                },
+                brightness: 3,
+                ..Default::default()
+            },
+            timer: SessionTimer {
+                length_secs: 3600,
+                show_countdown: false,
+            },
+            enable_3d: false,
+            presets: BTreeMap::from([("sunset".to_string(), rgba(0xff, 0x80, 0x00, 0xff))]),
+            keys,
+        };
+        assert_eq!(Settings::parse(&settings.to_text()), settings);
+    }
+
+    #[test]
+    fn invalid_values_fall_back_to_defaults() {
+        let settings = Settings::parse(concat!(
+            "version=3\n",
+            "top.mode=disco\n",
+            "bottom.cycle_colors=red,#12\n",
+            "top.cycle_interval=0\n",
+            "top.brightness=9\n",
+            "preset.red=#ff8000ff\n",
+            "preset.sun set=#ff8000ff\n",
+            "key.exit=L+Home\n",
+            "key.lunch=A\n",
+        ));
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn duplicate_key_combos_fall_back_to_defaults() {
+        let settings = Settings::parse("key.help=X\nkey.brighter=Start\n");
+        assert_eq!(settings.keys, KeyBindings::default());
+    }
+
+    #[test]
+    fn unknown_keys_and_comments_are_ignored() {
+        let settings = Settings::parse("# comment\nfoo=bar\n top.mode = exerciser \nbroken line\n");
+        assert_eq!(settings.top.mode, CookMode::Exerciser);
+    }
+
+    #[test]
+    fn file_without_version_is_read() {
+        let settings = Settings::parse("timer_length=600\n");
+        assert_eq!(settings.timer.length_secs, 600);
+    }
+
+    #[test]
+    fn version_1_is_migrated_to_both_screens() {
+        let settings = Settings::parse("version=1\nmode=color_cycle\ncycle_colors=red,blue\n");
+        for config in [&settings.top, &settings.bottom] {
+            assert_eq!(config.mode, CookMode::ColorCycle);
+            assert_eq!(config.cycle.colors, vec![CookColor::Red, CookColor::Blue]);
+        }
+    }
+
+    #[test]
+    fn version_2_brightness_is_migrated_to_both_screens() {
+        let settings = Settings::parse("version=2\nbrightness=4\n");
+        assert_eq!(settings.top.brightness, 4);
+        assert_eq!(settings.bottom.brightness, 4);
+    }
+
+    #[test]
+    fn preset_names_round_trip() {
+        let settings = Settings::parse(concat!(
+            "top.solid_color=sunset\n",
+            "top.cycle_colors=red,sunset\n",
+            "preset.sunset=#ff8000ff\n",
+        ));
+        let sunset = CookColor::custom(0xff, 0x80, 0x00, 0xff);
+        assert_eq!(settings.top.solid_color, sunset);
+        assert_eq!(settings.top.cycle.colors, vec![CookColor::Red, sunset]);
+        let text = settings.to_text();
+        assert!(text.contains("top.solid_color=sunset\n"));
+        assert_eq!(Settings::parse(&text), settings);
+    }
+
+    #[test]
+    fn presets_follow_the_builtin_colors_in_steps() {
+        let sunset = rgba(0xff, 0x80, 0x00, 0xff);
+        let presets = BTreeMap::from([("sunset".to_string(), sunset)]);
+        let last = COOK_COLORS[COOK_COLORS.len() - 1];
+        assert_eq!(last.step_with(true, &presets), CookColor::Custom(sunset));
+        assert_eq!(
+            CookColor::Custom(sunset).step_with(true, &presets),
+            COOK_COLORS[0]
+        );
+        assert_eq!(
+            COOK_COLORS[0].step_with(false, &presets),
+            CookColor::Custom(sunset)
+        );
+    }
+
+    #[test]
+    fn custom_color_text() {
+        let color = CookColor::parse("#ff800040").unwrap();
+        assert_eq!(color, CookColor::custom(0xff, 0x80, 0x00, 0x40));
+        assert_eq!(color.to_text(), "#ff800040");
+        assert_eq!(CookColor::parse("#ff80"), None);
+        assert_eq!(CookColor::parse("#+f800040"), None);
+    }
+}
@@ -1,12 +1,14 @@
 use core::str;
 use std::{
+    collections::BTreeMap,
     ffi::{c_char, CStr},
     fmt::{Display, Formatter},
     ops::Deref,
+    sync::{Mutex, OnceLock},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use crate::{c2d::rgba, platform::is_new_3ds};
+use crate::{color::rgba, platform::is_new_3ds};
 
 extern "C" {
     fn get_format_time() -> *mut c_char;
@@ -58,6 +60,16 @@ pub fn str_to_c_null_term_bytes(data: &str) -> Vec<u8> {
     format!("{}\0", data).into_bytes()
 }
 
+// 3725 -> "01:02:05"
+pub fn format_duration(secs: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
 pub fn ease_out_expo(elapsed: Duration, duration: Duration, start: f64, end: f64) -> f64 {
     if elapsed >= duration {
         return end;
@@ -67,8 +79,53 @@ pub fn ease_out_expo(elapsed: Duration, duration: Duration, start: f64, end: f64
             * (1.0 - 2.0_f64.powf(-10.0 * elapsed.as_millis() as f64 / duration.as_millis() as f64))
 }
 
+// xorshift32, cheap enough to run for every cell of every frame
+pub fn xorshift32(state: u32) -> u32 {
+    let mut x = if state == 0 { 0x9e37_79b9 } else { state };
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x
+}
+
+static COLOR_PRESETS: OnceLock<Mutex<BTreeMap<String, u32>>> = OnceLock::new();
+
+// named colors saved from the color picker
+fn color_presets() -> &'static Mutex<BTreeMap<String, u32>> {
+    COLOR_PRESETS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+pub fn get_color_presets() -> BTreeMap<String, u32> {
+    color_presets()
+        .lock()
+        .map(|presets| presets.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_color_presets(presets: &BTreeMap<String, u32>) {
+    if let Ok(mut current) = color_presets().lock() {
+        current.clone_from(presets);
+    }
+}
+
+// presets can not hide the built in names
+pub fn is_builtin_color_name(color: &str) -> bool {
+    builtin_color_rgba(color).is_some()
+}
+
 pub fn color_name_rgba(color: &str) -> u32 {
-    match color {
+    builtin_color_rgba(color)
+        .or_else(|| {
+            color_presets()
+                .lock()
+                .ok()
+                .and_then(|presets| presets.get(color).copied())
+        })
+        .unwrap_or(rgba(0x00, 0x00, 0x00, 0xff))
+}
+
+fn builtin_color_rgba(color: &str) -> Option<u32> {
+    Some(match color {
         // if there is a color tag, translate it
         "red" => rgba(0xff, 0x00, 0x00, 0xff),
         "green" => rgba(0x00, 0xff, 0x00, 0xff),
@@ -86,8 +143,8 @@ pub fn color_name_rgba(color: &str) -> u32 {
         "transparent" => rgba(0x0, 0x0, 0x0, 0x0),
         "tips" => rgba(0xaa, 0xaa, 0xaa, 0xff),
         "panel_bg" => rgba(0x26, 0x26, 0x26, 0xff),
-        _ => rgba(0x00, 0x00, 0x00, 0xff),
-    }
+        _ => return None,
+    })
 }
 
 pub async fn sleep_micros(micros: u64) {